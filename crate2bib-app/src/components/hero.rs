@@ -59,7 +59,7 @@ pub fn Hero() -> Element {
             }),
             None,
             None,
-            vec!["CITATION.cff", "citation.bib"],
+            crate2bib::CITATION_FILE_PATTERNS.to_vec(),
         )
         .await
         {
@@ -77,6 +77,33 @@ pub fn Hero() -> Element {
                                 crate_name.clone()
                             },
                         ),
+                        crate2bib::BibLaTeX::Repository(ref e) => (
+                            "repository".to_string(),
+                            e.url.clone().unwrap_or_default(),
+                            if let Some(v) = &e.version {
+                                format!("{crate_name} {}", v)
+                            } else {
+                                crate_name.clone()
+                            },
+                        ),
+                        crate2bib::BibLaTeX::Zenodo(ref e) => (
+                            "zenodo".to_string(),
+                            e.url.clone().unwrap_or_default(),
+                            if let Some(v) = &e.version {
+                                format!("{crate_name} {}", v)
+                            } else {
+                                crate_name.clone()
+                            },
+                        ),
+                        crate2bib::BibLaTeX::SoftwareMetadata(ref e) => (
+                            e.format.map_or("metadata".to_string(), |f| f.to_string()),
+                            e.url.clone().unwrap_or_default(),
+                            if let Some(v) = &e.version {
+                                format!("{crate_name} {}", v)
+                            } else {
+                                crate_name.clone()
+                            },
+                        ),
                         crate2bib::BibLaTeX::CITATIONCFF(ref e) => (
                             "CITATION.cff".to_string(),
                             e.url.clone().map_or("".to_string(), |x| format!("{x}")),
@@ -87,11 +114,9 @@ pub fn Hero() -> Element {
                                 crate_name.clone()
                             },
                         ),
-                        #[allow(unused)]
                         crate2bib::BibLaTeX::Plain(crate2bib::PlainBibLaTeX {
-                            ref bibliography,
                             ref repository,
-                            ref filename,
+                            ..
                         }) => (
                             "bibliography file".to_string(),
                             repository.clone(),
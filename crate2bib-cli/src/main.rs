@@ -1,91 +1,915 @@
-use clap::Parser;
+use clap::{Parser, Subcommand};
+
+/// Formats in which bibliographies can be printed
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum OutputFormat {
+    /// BibLaTeX entries
+    Biblatex,
+    /// Hayagriva YAML as used by Typst
+    Hayagriva,
+    /// All results together with their source, rank and diagnostics as JSON
+    Json,
+    /// Like json but with one crate per line (JSON Lines)
+    Jsonl,
+}
+
+impl OutputFormat {
+    fn is_json(&self) -> bool {
+        matches!(self, OutputFormat::Json | OutputFormat::Jsonl)
+    }
+}
+
+/// Sources of results which can be selected with `--source`
+#[derive(clap::ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum SourceFilter {
+    /// Entries generated from the information of crates.io or another registry
+    CratesIo,
+    /// CITATION.cff files inside the repository
+    Cff,
+    /// .zenodo.json or codemeta.json files inside the repository
+    Metadata,
+    /// Bibliography files inside the repository
+    Bib,
+    /// Entries generated from the metadata of the repository
+    Repository,
+    /// Entries resolved via doi.org
+    Doi,
+    /// Archived releases on Zenodo
+    Zenodo,
+}
+
+impl SourceFilter {
+    fn matches(&self, result: &crate2bib::BibLaTeX) -> bool {
+        use crate2bib::BibLaTeX;
+        match (self, result) {
+            (SourceFilter::CratesIo, BibLaTeX::CratesIO(_))
+            | (SourceFilter::Cff, BibLaTeX::CITATIONCFF(_))
+            | (SourceFilter::Metadata, BibLaTeX::SoftwareMetadata(_))
+            | (SourceFilter::Repository, BibLaTeX::Repository(_))
+            | (SourceFilter::Zenodo, BibLaTeX::Zenodo(_)) => true,
+            (SourceFilter::Bib, BibLaTeX::Plain(p)) => p.doi.is_none(),
+            (SourceFilter::Doi, BibLaTeX::Plain(p)) => p.doi.is_some(),
+            _ => false,
+        }
+    }
+}
 
 #[derive(Parser, Debug)]
 #[command(
     version,
-    about = "Creates a BibTeX entry given a crate name and version number.\
-        Note: This crate respects semver.",
+    about = "Creates BibLaTeX entries for crates, Cargo files, repositories and DOIs.",
     long_about = None,
     author,
 )]
-struct Args {
-    /// The exact name of the crate. Note that underscores are synonymous as dashes in the API of
-    /// crates.io.
-    crate_name: String,
-    /// A semver compliant version number (eg. "1", 0.1", "0.3.38").
-    #[arg(short, long, default_value = "")]
-    ver: String,
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+    #[command(flatten)]
+    client: ClientArgs,
+    #[command(flatten)]
+    output: OutputArgs,
+}
+
+/// Configuration of the client which is shared by all subcommands
+#[derive(clap::Args, Debug)]
+struct ClientArgs {
     /// The name of the user-agent. Automation tools
     /// should specify this variable to specify which
     /// user generates the requests.
     #[arg(
         short,
         long,
+        global = true,
         default_value_t = format!("crate2bib-cli-user-agent"),
         verbatim_doc_comment
     )]
     user_agent: String,
-    #[arg(long, default_values_t = [
-        "CITATION.cff".to_string(),
-        "citation.bib".to_string(),
-    ])]
+    /// Case-insensitive glob patterns of citation files (eg. "**/*.bib").
+    /// The format can be given explicitly as in "refs.txt:bib".
+    /// Defaults to conventional names such as CITATION.cff or paper/paper.bib.
+    #[arg(
+        long,
+        global = true,
+        default_values_t = crate2bib::CITATION_FILE_PATTERNS.iter().map(|x| x.to_string()),
+        verbatim_doc_comment
+    )]
     filenames: Vec<String>,
+}
+
+impl ClientArgs {
+    fn user_agent(&self) -> Option<&str> {
+        if self.user_agent.is_empty() {
+            None
+        } else {
+            Some(self.user_agent.as_str())
+        }
+    }
+
+    fn filenames(&self) -> Vec<&str> {
+        self.filenames.iter().map(|x| x.as_str()).collect()
+    }
+}
+
+/// Options which control the selection and formatting of results
+#[derive(clap::Args, Debug)]
+struct OutputArgs {
+    /// Write non-ASCII characters as raw "utf8" or as LaTeX commands with "latex".
+    #[arg(long, global = true, default_value = "utf8")]
+    encoding: crate2bib::Encoding,
+    /// Output format of the generated entries.
+    #[arg(short, long, global = true, value_enum, default_value_t = OutputFormat::Biblatex)]
+    format: OutputFormat,
+    /// Print formatted references in the "apa", "ieee", "chicago" or "vancouver" style instead
+    /// of bibliography entries.
+    #[arg(short, long, global = true, verbatim_doc_comment)]
+    style: Option<crate2bib::CitationStyle>,
+    /// Print formatted references in the style of a custom ".csl" file.
+    #[arg(long, global = true, conflicts_with = "style")]
+    csl: Option<std::path::PathBuf>,
+    /// Markup of formatted references: "text", "markdown" or "html".
+    #[arg(long, global = true, default_value = "text")]
+    markup: crate2bib::Markup,
+    /// Write the output into this file instead of stdout.
+    #[arg(short, long, global = true)]
+    output: Option<std::path::PathBuf>,
+    /// Append to the file given by --output instead of overwriting it.
+    /// Can not be combined with "--format json" since the file would not be valid JSON.
+    #[arg(long, global = true, requires = "output")]
+    append: bool,
+    /// Only keep the preferred result.
+    #[arg(long, global = true, overrides_with = "all")]
+    best: bool,
+    /// Keep all results (default).
+    #[arg(long, global = true, overrides_with = "best")]
+    all: bool,
+    /// Only keep results from the given sources (eg. "crates-io,cff").
+    #[arg(long, global = true, value_enum, value_delimiter = ',')]
+    source: Vec<SourceFilter>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Cite crates hosted on crates.io or another registry.
+    Crate(CrateArgs),
+    /// Cite every package of a Cargo.lock file at its locked version.
+    Lock {
+        /// Path of the lockfile. Path dependencies are searched for next to it.
+        #[arg(default_value = "Cargo.lock")]
+        path: std::path::PathBuf,
+    },
+    /// Cite the dependencies of a Cargo.toml file.
+    Manifest {
+        /// Path of the manifest.
+        #[arg(default_value = "Cargo.toml")]
+        path: std::path::PathBuf,
+        /// Also cite dev- and build-dependencies.
+        #[arg(long)]
+        dev: bool,
+        /// Number of crates which are cited at the same time.
+        #[arg(short, long, default_value_t = 4)]
+        jobs: usize,
+    },
+    /// Cite a repository directly without going through crates.io.
+    Repo {
+        /// Url of the repository.
+        url: String,
+        /// Branch, tag or commit which should be cited.
+        #[arg(long)]
+        rev: Option<String>,
+    },
+    /// Resolve a DOI via doi.org.
+    Doi {
+        /// The DOI with or without the "https://doi.org/" prefix.
+        doi: String,
+    },
+    /// Convert a local CITATION.cff file without any network access.
+    Cff {
+        /// Path of the CITATION.cff file.
+        #[arg(default_value = "CITATION.cff")]
+        path: std::path::PathBuf,
+    },
+    /// Write a CITATION.cff file for the package of a Cargo.toml file.
+    InitCff {
+        /// Path of the CITATION.cff file which is written.
+        #[arg(default_value = "CITATION.cff")]
+        path: std::path::PathBuf,
+        /// Path of the manifest of the package.
+        #[arg(long, default_value = "Cargo.toml")]
+        manifest: std::path::PathBuf,
+        /// Overwrite an existing file.
+        #[arg(long)]
+        force: bool,
+    },
+}
+
+#[derive(clap::Args, Debug)]
+struct CrateArgs {
+    /// The exact names of the crates optionally followed by a version requirement as in
+    /// "serde@1.0". Note that underscores are synonymous as dashes in the API of crates.io.
+    /// Multiple crates are cited concurrently and combined into a single bibliography.
+    #[arg(
+        required_unless_present_any = ["scan", "from_file"],
+        verbatim_doc_comment
+    )]
+    crate_names: Vec<String>,
+    /// Read additional crates from a file with one "name[@requirement]" per line.
+    /// Use "-" to read from stdin.
+    #[arg(long, verbatim_doc_comment)]
+    from_file: Option<std::path::PathBuf>,
+    /// Number of crates which are cited at the same time in batch mode.
+    #[arg(short, long, default_value_t = 4)]
+    jobs: usize,
+    /// A semver compliant version requirement (eg. "1", 0.1", "0.3.38", ">=0.3, <0.5").
+    #[arg(short, long, default_value = "")]
+    ver: String,
+    /// Also consider versions which were yanked.
+    /// Yanked versions are always considered when pinned exactly (eg. "=0.3.38").
+    #[arg(long, verbatim_doc_comment)]
+    include_yanked: bool,
+    /// Also consider pre-releases.
+    #[arg(long)]
+    include_prerelease: bool,
+    /// Select the oldest instead of the newest matching version.
+    #[arg(long)]
+    oldest: bool,
+    /// Check RustSec advisories for unmaintained crates.
+    #[arg(long)]
+    advisories: bool,
     #[arg(short, long, default_value = "")]
     branch_name: String,
+    /// Name of a registry in `.cargo/config.toml` or url of a sparse index.
+    /// Defaults to crates.io.
+    #[arg(short, long, default_value = "", verbatim_doc_comment)]
+    registry: String,
+    /// Combine all found entries field by field into a single entry.
+    #[arg(short, long, conflicts_with = "best")]
+    merge: bool,
+    /// Add the Software Heritage identifier (SWHID) of the cited version.
+    #[arg(long)]
+    swhid: bool,
+    /// Request archival by Software Heritage if the repository was not archived yet.
+    /// Implies --swhid.
+    #[arg(long, verbatim_doc_comment)]
+    swh_save: bool,
+    /// Add the crate to an existing bibliography file or update its entry if it was generated
+    /// previously. Manual edits, comments and @string macros are preserved.
+    #[arg(long, verbatim_doc_comment)]
+    update: Option<std::path::PathBuf>,
+    /// Write the updated bibliography without asking for confirmation.
+    #[arg(short, long)]
+    yes: bool,
+    /// Scan LaTeX (.tex) or Markdown (.md, .qmd) files for citations of crates such as
+    /// \cite{crate:serde} or @crate:serde@1.0 and print a bibliography of all cited crates.
+    #[arg(long, num_args = 1.., verbatim_doc_comment)]
+    scan: Vec<std::path::PathBuf>,
+    /// Prefix of citation keys which refer to crates.
+    #[arg(long, default_value = crate2bib::DEFAULT_CITE_PREFIX)]
+    cite_prefix: String,
 }
 
 #[async_std::main]
 async fn main() -> crate2bib::Result<()> {
     env_logger::init();
 
-    let args = Args::parse();
-    let filenames = args.filenames.iter().map(|x| x.as_str()).collect();
-    let results = crate2bib::get_biblatex(
-        &args.crate_name,
-        if args.ver.is_empty() {
-            None
+    let cli = Cli::parse();
+    check_output_args(&cli.output)?;
+    let client = crate2bib::build_client(cli.client.user_agent())?;
+    match &cli.command {
+        Command::Crate(args) => cite_crates(args, &cli, &client).await,
+        Command::Lock { path } => cite_lockfile(path, &cli).await,
+        Command::Manifest { path, dev, jobs } => {
+            let manifest = std::fs::read_to_string(path)?;
+            let workspace_manifest = find_workspace_manifest(path);
+            let dependencies =
+                crate2bib::manifest_dependencies(&manifest, workspace_manifest.as_deref(), *dev)?;
+            for name in dependencies.skipped.iter() {
+                eprintln!(
+                    "Warning: skipping {name} which is not a crates.io dependency; \
+                    use \"crate2bib lock\" to cite it"
+                );
+            }
+            cite_batch(&dependencies.requests, &cli, &Default::default(), *jobs).await
+        }
+        Command::Repo { url, rev } => {
+            let results = crate2bib::get_biblatex_repository(
+                url,
+                rev.as_deref(),
+                cli.client.user_agent(),
+                cli.client.filenames(),
+            )
+            .await?;
+            write_results(url, rev.as_deref(), results, vec![], &cli.output)
+        }
+        Command::Doi { doi } => {
+            let bibliography =
+                crate2bib::get_bibtex_doi_with_url(doi, crate2bib::DOI_URL, &client).await?;
+            let doi = crate2bib::normalize_doi(doi);
+            let result = crate2bib::BibLaTeX::Plain(crate2bib::PlainBibLaTeX {
+                bibliography,
+                repository: format!("https://doi.org/{doi}"),
+                filename: String::new(),
+                doi: Some(doi.to_string()),
+                selected: vec![],
+                discovered: false,
+            });
+            write_results(doi, None, vec![result], vec![], &cli.output)
+        }
+        Command::Cff { path } => {
+            let result = crate2bib::parse_citation_cff(&std::fs::read_to_string(path)?)?;
+            write_results(
+                &path.to_string_lossy(),
+                None,
+                vec![result],
+                vec![],
+                &cli.output,
+            )
+        }
+        Command::InitCff {
+            path,
+            manifest,
+            force,
+        } => {
+            let out = &cli.output;
+            if out.format != OutputFormat::Biblatex
+                || out.style.is_some()
+                || out.csl.is_some()
+                || out.output.is_some()
+                || out.best
+                || !out.source.is_empty()
+            {
+                return Err(crate2bib::Err::InvalidArguments(
+                    "--format, --style, --csl, --output, --best and --source can not be used with init-cff"
+                        .to_string(),
+                ));
+            }
+            if path.exists() && !force {
+                eprintln!("Pass --force to overwrite {}", path.display());
+                return Err(crate2bib::Err::FileExists(path.clone()));
+            }
+            let workspace_manifest = find_workspace_manifest(manifest);
+            let entry = crate2bib::manifest_to_biblatex(
+                &std::fs::read_to_string(manifest)?,
+                workspace_manifest.as_deref(),
+            )?;
+            std::fs::write(path, entry.to_citation_cff()?)?;
+            eprintln!("Wrote {}", path.display());
+            Ok(())
+        }
+    }
+}
+
+/// Reads the manifest of the workspace in one of the parent directories of the given manifest
+fn find_workspace_manifest(manifest: &std::path::Path) -> Option<String> {
+    let manifest = std::fs::canonicalize(manifest).ok()?;
+    manifest.ancestors().skip(1).find_map(|dir| {
+        let contents = std::fs::read_to_string(dir.join("Cargo.toml")).ok()?;
+        contents
+            .lines()
+            .any(|l| l.trim() == "[workspace]" || l.trim().starts_with("[workspace."))
+            .then_some(contents)
+    })
+}
+
+/// Cites crates from crates.io or another registry
+async fn cite_crates(
+    args: &CrateArgs,
+    cli: &Cli,
+    client: &reqwest::Client,
+) -> crate2bib::Result<()> {
+    let out = &cli.output;
+    let user_agent = cli.client.user_agent();
+    let filenames = cli.client.filenames();
+    let version = if args.ver.is_empty() {
+        None
+    } else {
+        Some(args.ver.as_str())
+    };
+    let branch_name = if args.branch_name.is_empty() {
+        None
+    } else {
+        Some(args.branch_name.as_str())
+    };
+    let options = crate2bib::ResolveOptions {
+        include_yanked: args.include_yanked,
+        include_prerelease: args.include_prerelease,
+        oldest: args.oldest,
+        advisories: args.advisories,
+    };
+    if !args.scan.is_empty() {
+        if args.merge
+            || args.update.is_some()
+            || !args.registry.is_empty()
+            || version.is_some()
+            || branch_name.is_some()
+            || args.swhid
+            || args.swh_save
+        {
+            return Err(crate2bib::Err::InvalidArguments(
+                "--merge, --update, --registry, --ver, --branch-name, --swhid and --swh-save \
+                can not be used with --scan"
+                    .to_string(),
+            ));
+        }
+        let paths: Vec<_> = args.scan.iter().map(|p| p.as_path()).collect();
+        let cited = crate2bib::scan_documents(&paths, &args.cite_prefix)?;
+        let results: Vec<_> =
+            crate2bib::get_biblatex_cited(&cited, &options, user_agent, filenames)
+                .await?
+                .into_iter()
+                .map(|(c, result)| (c, result.map(|r| select_results(r, out))))
+                .collect();
+        for (c, result) in results.iter() {
+            if let Err(e) = result {
+                eprintln!("Warning: could not cite {}: {e}", c.key);
+            }
+        }
+        if out.format.is_json() {
+            let reports: Vec<_> = results
+                .iter()
+                .map(|(c, result)| {
+                    let mut report = match result {
+                        Ok(r) => crate2bib::CitationReport::new(&c.name, c.version.as_deref(), r),
+                        Err(e) => {
+                            crate2bib::CitationReport::from_error(&c.name, c.version.as_deref(), e)
+                        }
+                    };
+                    report.cite_key = Some(c.key.clone());
+                    report
+                })
+                .collect();
+            return write_output(out, &reports_to_string(&reports, out.format)?);
+        }
+        let entries = crate2bib::cited_entries(&results)?;
+        return write_output(out, &render_entries(&entries, out)?);
+    }
+
+    let mut requests = args
+        .crate_names
+        .iter()
+        .map(|x| x.parse())
+        .collect::<crate2bib::Result<Vec<crate2bib::CrateRequest>>>()?;
+    if let Some(path) = &args.from_file {
+        let list = if path.as_os_str() == "-" {
+            std::io::read_to_string(std::io::stdin())?
         } else {
-            Some(&args.ver)
-        },
-        if args.user_agent.is_empty() {
-            None
+            std::fs::read_to_string(path)?
+        };
+        requests.extend(crate2bib::parse_crate_list(&list)?);
+    }
+    if requests.len() != 1 || args.from_file.is_some() {
+        if args.merge
+            || args.update.is_some()
+            || !args.registry.is_empty()
+            || version.is_some()
+            || branch_name.is_some()
+            || args.swhid
+            || args.swh_save
+        {
+            return Err(crate2bib::Err::InvalidArguments(
+                "--merge, --update, --registry, --ver, --branch-name, --swhid and --swh-save \
+                can only be used with a single crate"
+                    .to_string(),
+            ));
+        }
+        return cite_batch(&requests, cli, &options, args.jobs).await;
+    }
+    let request = requests.remove(0);
+    let crate_name = request.name.clone();
+    let version = request.version.as_deref().or(version);
+    let results = if args.registry.is_empty() {
+        crate2bib::get_biblatex_with_options(
+            &crate_name,
+            version,
+            &options,
+            user_agent,
+            branch_name,
+            filenames,
+        )
+        .await?
+    } else {
+        let registry = if args.registry.contains("://") {
+            crate2bib::Registry::from_url(&args.registry)
         } else {
-            Some(&args.user_agent)
-        },
-        if args.branch_name.is_empty() {
-            None
+            crate2bib::Registry::from_cargo_config(&args.registry, &std::env::current_dir()?)?
+        };
+        crate2bib::get_biblatex_registry(
+            &crate_name,
+            version,
+            &options,
+            &registry,
+            user_agent,
+            branch_name,
+            filenames,
+        )
+        .await?
+    };
+    let mut results = select_results(results, out);
+
+    let mut diagnostics = vec![];
+    if args.swhid || args.swh_save {
+        for result in results.iter_mut() {
+            if let crate2bib::BibLaTeX::CratesIO(b) | crate2bib::BibLaTeX::Repository(b) = result {
+                let url = b.url.clone().unwrap_or_default();
+                match crate2bib::add_swhid(
+                    client,
+                    crate2bib::SWH_API_URL,
+                    b,
+                    Some(&crate_name),
+                    None,
+                    args.swh_save,
+                )
+                .await
+                {
+                    Ok(Some(_)) => (),
+                    Ok(None) => diagnostics.push(crate2bib::Diagnostic::warning(format!(
+                        "could not find a SWHID for {url}"
+                    ))),
+                    Err(e) => diagnostics.push(crate2bib::Diagnostic::warning(format!(
+                        "could not query Software Heritage for {url}: {e}"
+                    ))),
+                }
+            }
+        }
+    }
+
+    if let Some(path) = &args.update {
+        print_diagnostics(&crate_name, &results, &diagnostics);
+        let entries = if args.merge {
+            crate2bib::merge_biblatex(&results, &Default::default())
+                .map(|m| vec![m.entry.to_entry()])
+                .unwrap_or_default()
+        } else {
+            match results.first() {
+                Some(r) => crate2bib::result_entries(r)?,
+                None => vec![],
+            }
+        };
+        let entries: Vec<_> = entries
+            .into_iter()
+            .map(|e| (crate_name.clone(), e))
+            .collect();
+        let existing = if path.exists() {
+            std::fs::read_to_string(path)?
         } else {
-            Some(&args.branch_name)
+            String::new()
+        };
+        let update = crate2bib::update_bibliography(&existing, &entries, out.encoding)?;
+        if update.is_unchanged() {
+            eprintln!("{} is already up to date", path.display());
+            return Ok(());
+        }
+        eprint!("{}", update.diff(3));
+        if !args.yes {
+            use std::io::{IsTerminal, Write};
+            if !std::io::stdin().is_terminal() {
+                eprintln!("Not writing changes without confirmation; pass --yes");
+                return Ok(());
+            }
+            eprint!("Write changes to {}? [y/N] ", path.display());
+            std::io::stderr().flush()?;
+            let mut answer = String::new();
+            std::io::stdin().read_line(&mut answer)?;
+            if !matches!(answer.trim(), "y" | "Y" | "yes") {
+                return Ok(());
+            }
+        }
+        std::fs::write(path, update.updated)?;
+        return Ok(());
+    }
+
+    if args.merge {
+        print_diagnostics(&crate_name, &results, &diagnostics);
+        let merged = crate2bib::merge_biblatex(&results, &Default::default());
+        if out.format.is_json() {
+            let mut report = crate2bib::CitationReport::new(&crate_name, version, &results);
+            report.merged = merged;
+            report.diagnostics.extend(diagnostics);
+            return write_output(out, &report_to_string(&report, out.format)?);
+        }
+        if let Some(merged) = merged {
+            let sources = merged
+                .provenance
+                .iter()
+                .map(|(field, source)| format!("{field} from {source}"))
+                .collect::<Vec<_>>()
+                .join(", ");
+            eprintln!("Merged entry with {sources}");
+            return write_output(out, &render_entries(&[merged.entry.to_entry()], out)?);
+        }
+        return Ok(());
+    }
+
+    write_results(&crate_name, version, results, diagnostics, out)
+}
+
+/// Cites every package of a `Cargo.lock` file and writes a single deduplicated bibliography
+async fn cite_lockfile(path: &std::path::Path, cli: &Cli) -> crate2bib::Result<()> {
+    let out = &cli.output;
+    let results =
+        crate2bib::get_biblatex_lockfile(path, cli.client.user_agent(), cli.client.filenames())
+            .await?;
+    let results: Vec<_> = results
+        .into_iter()
+        .map(|(package, result)| (package, result.map(|r| select_results(r, out))))
+        .collect();
+    for (package, result) in results.iter() {
+        match result {
+            Ok(r) => print_diagnostics(&package.name, r, &[]),
+            Err(e) => eprintln!("Warning: could not cite {}: {e}", package.name),
+        }
+    }
+
+    if out.format.is_json() {
+        let reports: Vec<_> = results
+            .iter()
+            .map(|(package, result)| {
+                let version = format!("={}", package.version);
+                match result {
+                    Ok(r) => crate2bib::CitationReport::new(&package.name, Some(&version), r),
+                    Err(e) => {
+                        crate2bib::CitationReport::from_error(&package.name, Some(&version), e)
+                    }
+                }
+            })
+            .collect();
+        return write_output(out, &reports_to_string(&reports, out.format)?);
+    }
+    let mut bibliography = crate2bib::AggregatedBibliography::default();
+    for (package, result) in results.iter() {
+        if let Ok(result) = result {
+            bibliography.add(&package.name, result)?;
+        }
+    }
+    let entries = bibliography.bibliography().into_vec();
+    write_output(out, &render_entries(&entries, out)?)
+}
+
+/// Cites many crates concurrently and writes a single deduplicated bibliography
+async fn cite_batch(
+    requests: &[crate2bib::CrateRequest],
+    cli: &Cli,
+    options: &crate2bib::ResolveOptions,
+    jobs: usize,
+) -> crate2bib::Result<()> {
+    let out = &cli.output;
+    let total = requests.len();
+    let mut finished = 0;
+    let results = crate2bib::get_biblatex_batch(
+        requests,
+        options,
+        cli.client.user_agent(),
+        cli.client.filenames(),
+        jobs,
+        |request, result| {
+            finished += 1;
+            match result {
+                Ok(_) => eprintln!("[{finished}/{total}] Cited {request}"),
+                Err(e) => eprintln!("[{finished}/{total}] Warning: could not cite {request}: {e}"),
+            }
         },
-        filenames,
     )
     .await?;
+    let results: Vec<_> = results
+        .into_iter()
+        .map(|(request, result)| (request, result.map(|r| select_results(r, out))))
+        .collect();
+    for (request, result) in results.iter() {
+        if let Ok(r) = result {
+            print_diagnostics(&request.name, r, &[]);
+        }
+    }
+
+    if out.format.is_json() {
+        let reports: Vec<_> = results
+            .iter()
+            .map(|(request, result)| match result {
+                Ok(r) => {
+                    crate2bib::CitationReport::new(&request.name, request.version.as_deref(), r)
+                }
+                Err(e) => crate2bib::CitationReport::from_error(
+                    &request.name,
+                    request.version.as_deref(),
+                    e,
+                ),
+            })
+            .collect();
+        return write_output(out, &reports_to_string(&reports, out.format)?);
+    }
+    let bibliography = crate2bib::batch_bibliography(&results)?;
+    let entries = bibliography.bibliography().into_vec();
+    write_output(out, &render_entries(&entries, out)?)
+}
+
+/// Prints the warnings of all results to stderr
+fn print_diagnostics(
+    name: &str,
+    results: &[crate2bib::BibLaTeX],
+    diagnostics: &[crate2bib::Diagnostic],
+) {
+    for result in results.iter() {
+        for diagnostic in result.diagnostics(name) {
+            eprintln!("Warning: {}", diagnostic.message);
+        }
+    }
+    for diagnostic in diagnostics.iter() {
+        eprintln!("Warning: {}", diagnostic.message);
+    }
+}
+
+/// Lists the citation files which were found in the repository of the entry
+fn describe_citation_files(entry: &crate2bib::BibLaTeXCratesIO) -> String {
+    if entry.citation_files.is_empty() {
+        return String::new();
+    }
+    format!(
+        " (found citation files {} in repository)",
+        entry.citation_files.join(", ")
+    )
+}
 
+/// Describes where a result was obtained from
+fn describe_result(result: &crate2bib::BibLaTeX) -> String {
+    match result {
+        crate2bib::BibLaTeX::CITATIONCFF(b) => format!(
+            "Generated from CITATION.cff file in repository {}",
+            b.repository
+                .as_ref()
+                .map_or("".to_string(), |x| format!("{x}"))
+        ),
+        crate2bib::BibLaTeX::SoftwareMetadata(m) => format!(
+            "Generated from {} file",
+            m.format.map_or("metadata".to_string(), |f| f.to_string())
+        ),
+        crate2bib::BibLaTeX::CratesIO(b) => format!(
+            "Generated entry from crates.io information{}",
+            describe_citation_files(b)
+        ),
+        crate2bib::BibLaTeX::Repository(b) => format!(
+            "Generated entry from metadata of repository {}{}",
+            b.url.as_deref().unwrap_or_default(),
+            describe_citation_files(b)
+        ),
+        crate2bib::BibLaTeX::Zenodo(b) => format!(
+            "Obtained archived release from zenodo {}",
+            b.doi.as_deref().unwrap_or_default()
+        ),
+        crate2bib::BibLaTeX::Plain(crate2bib::PlainBibLaTeX {
+            repository,
+            filename,
+            doi: Some(doi),
+            discovered: true,
+            ..
+        }) => format!("Resolved DOI {doi} found in {filename} of repository {repository}"),
+        crate2bib::BibLaTeX::Plain(crate2bib::PlainBibLaTeX {
+            repository,
+            filename,
+            ..
+        }) => {
+            format!("Obtained bibliography {filename} file directly from repository {repository}")
+        }
+    }
+}
+
+/// Writes all results of a single citation as a report or as entries while describing their
+/// origin on stderr
+fn write_results(
+    name: &str,
+    version: Option<&str>,
+    results: Vec<crate2bib::BibLaTeX>,
+    diagnostics: Vec<crate2bib::Diagnostic>,
+    out: &OutputArgs,
+) -> crate2bib::Result<()> {
+    let results = select_results(results, out);
+    print_diagnostics(name, &results, &diagnostics);
+    if out.format.is_json() {
+        let mut report = crate2bib::CitationReport::new(name, version, &results);
+        report.diagnostics.extend(diagnostics);
+        return write_output(out, &report_to_string(&report, out.format)?);
+    }
+    let mut entries = vec![];
     for result in results {
-        match result {
-            crate2bib::BibLaTeX::CITATIONCFF(ref b) => {
-                println!(
-                    "Generated from CITATION.cff file in repository {}",
-                    b.repository
-                        .as_ref()
-                        .map_or("".to_string(), |x| format!("{x}"))
-                )
-            }
-            crate2bib::BibLaTeX::CratesIO(_) => {
-                println!("Generated enty from crates.io information")
-            }
-            #[allow(unused)]
-            crate2bib::BibLaTeX::Plain(crate2bib::PlainBibLaTeX {
-                ref bibliography,
-                ref repository,
-                ref filename,
-            }) => {
-                println!(
-                    "Obtained bibliography {filename} file directly from repository {repository}"
-                )
-            }
+        eprintln!("{}", describe_result(&result));
+        entries.extend(crate2bib::result_entries(&result)?);
+    }
+    write_output(out, &render_entries(&entries, out)?)
+}
+
+/// Applies the `--source` filters and `--best` selection
+fn select_results(results: Vec<crate2bib::BibLaTeX>, out: &OutputArgs) -> Vec<crate2bib::BibLaTeX> {
+    let mut results: Vec<_> = results
+        .into_iter()
+        .filter(|r| out.source.is_empty() || out.source.iter().any(|s| s.matches(r)))
+        .collect();
+    if out.best {
+        results.truncate(1);
+    }
+    results
+}
+
+/// Formats entries as references if a style was given or in the selected output format
+fn render_entries(
+    entries: &[crate2bib::biblatex::Entry],
+    out: &OutputArgs,
+) -> crate2bib::Result<String> {
+    if let Some(style) = out.style {
+        return crate2bib::format_references(entries, style, out.markup);
+    }
+    if let Some(path) = &out.csl {
+        let style = crate2bib::csl_style_from_xml(&std::fs::read_to_string(path)?)?;
+        return crate2bib::format_references_csl(entries, &style, out.markup);
+    }
+    Ok(match out.format {
+        OutputFormat::Biblatex => crate2bib::entries_to_biblatex_string(entries, out.encoding),
+        OutputFormat::Hayagriva => crate2bib::entries_to_hayagriva(entries)?,
+        OutputFormat::Json | OutputFormat::Jsonl => unreachable!(),
+    })
+}
+
+/// Formats a single report as indented JSON or as a single line
+fn report_to_string(
+    report: &crate2bib::CitationReport,
+    format: OutputFormat,
+) -> crate2bib::Result<String> {
+    match format {
+        OutputFormat::Jsonl => report.to_json_line(),
+        _ => report.to_json(),
+    }
+}
+
+/// Formats reports as a JSON array or with one report per line
+fn reports_to_string(
+    reports: &[crate2bib::CitationReport],
+    format: OutputFormat,
+) -> crate2bib::Result<String> {
+    if format == OutputFormat::Jsonl {
+        Ok(reports
+            .iter()
+            .map(|r| r.to_json_line())
+            .collect::<crate2bib::Result<Vec<_>>>()?
+            .join("\n"))
+    } else {
+        crate2bib::reports_to_json(reports)
+    }
+}
+
+/// Rejects combinations of output options which would produce an invalid file
+fn check_output_args(out: &OutputArgs) -> crate2bib::Result<()> {
+    if out.append && out.format == OutputFormat::Json {
+        return Err(crate2bib::Err::InvalidArguments(
+            "--append can not be used with --format json; use --format jsonl instead".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Writes the output to stdout or the file given by `--output`
+fn write_output(out: &OutputArgs, content: &str) -> crate2bib::Result<()> {
+    use std::io::Write;
+    let content = content.trim_end();
+    let Some(path) = &out.output else {
+        if !content.is_empty() {
+            println!("{content}");
         }
-        println!("{result}");
+        return Ok(());
+    };
+    let mut file = std::fs::OpenOptions::new()
+        .create(true)
+        .write(true)
+        .append(out.append)
+        .truncate(!out.append)
+        .open(path)?;
+    if out.append && file.metadata()?.len() > 0 && out.format != OutputFormat::Jsonl {
+        writeln!(file)?;
+    }
+    if !content.is_empty() {
+        writeln!(file, "{content}")?;
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reject_appending_json() {
+        let parse = |format: &str| {
+            Cli::try_parse_from([
+                "crate2bib",
+                "lock",
+                "-o",
+                "out.json",
+                "--append",
+                "-f",
+                format,
+            ])
+            .unwrap()
+        };
+        assert!(matches!(
+            check_output_args(&parse("json").output),
+            Err(crate2bib::Err::InvalidArguments(_))
+        ));
+        assert!(check_output_args(&parse("jsonl").output).is_ok());
+        assert!(check_output_args(&parse("biblatex").output).is_ok());
+    }
+}
@@ -0,0 +1,182 @@
+use serde::{Deserialize, Serialize};
+
+/// Base url of the [OSV](https://osv.dev) API which mirrors the
+/// [RustSec advisory database](https://rustsec.org)
+pub const OSV_API_URL: &str = "https://api.osv.dev";
+
+/// Type of a [Notice] attached to a crate
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum NoticeKind {
+    /// The crate was marked as deprecated by its authors
+    Deprecated,
+    /// The crate is reported as unmaintained by a RustSec advisory
+    Unmaintained,
+}
+
+/// Informs that a cited crate should probably not be used anymore
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Notice {
+    /// Type of the notice
+    pub kind: NoticeKind,
+    /// Identifier of the advisory such as `RUSTSEC-2021-0139`
+    pub id: Option<String>,
+    /// Short description of the notice
+    pub message: String,
+}
+
+impl std::fmt::Display for Notice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.kind {
+            NoticeKind::Deprecated => write!(f, "deprecated")?,
+            NoticeKind::Unmaintained => write!(f, "unmaintained")?,
+        }
+        if let Some(id) = &self.id {
+            write!(f, " ({id})")?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+/// Queries the RustSec advisories for the given crate and version and returns all which mark it
+/// as unmaintained.
+///
+/// The advisories are obtained via the [OSV](https://osv.dev) API located at `base_url`
+/// (see [OSV_API_URL]).
+pub async fn rustsec_notices(
+    client: &reqwest::Client,
+    base_url: &str,
+    crate_name: &str,
+    version: Option<&semver::Version>,
+) -> crate::Result<Vec<Notice>> {
+    let mut query = serde_json::json!({
+        "package": {"name": crate_name, "ecosystem": "crates.io"}
+    });
+    if let Some(version) = version {
+        query["version"] = serde_json::Value::String(version.to_string());
+    }
+    #[cfg(feature = "log")]
+    log::trace!("Requesting advisories for crate {crate_name}");
+    let response = client
+        .post(format!("{}/v1/query", base_url.trim_end_matches('/')))
+        .json(&query)
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<serde_json::Value>()
+        .await?;
+
+    Ok(response
+        .get("vulns")
+        .and_then(|v| v.as_array())
+        .map(|vulns| {
+            vulns
+                .iter()
+                .filter(|v| {
+                    v.get("database_specific")
+                        .and_then(|d| d.get("informational"))
+                        .and_then(|i| i.as_str())
+                        == Some("unmaintained")
+                })
+                .filter(|v| v.get("withdrawn").is_none())
+                .map(|v| Notice {
+                    kind: NoticeKind::Unmaintained,
+                    id: v.get("id").and_then(|x| x.as_str()).map(|x| x.to_string()),
+                    message: v
+                        .get("summary")
+                        .and_then(|x| x.as_str())
+                        .unwrap_or("crate is unmaintained")
+                        .to_string(),
+                })
+                .collect()
+        })
+        .unwrap_or_default())
+}
+
+/// Checks if the description or keywords of a crate mark it as deprecated.
+///
+/// Only descriptions which start with a notice such as `DEPRECATED: use other_crate` or
+/// `This crate is deprecated` are considered since crates may merely deal with deprecated items.
+pub(crate) fn deprecation_notice<'a>(
+    description: Option<&str>,
+    keywords: impl IntoIterator<Item = &'a str>,
+) -> Option<Notice> {
+    let in_description = description.is_some_and(|d| {
+        let d = d
+            .trim_start_matches(|c: char| !c.is_alphanumeric())
+            .to_lowercase();
+        [
+            "deprecated",
+            "this crate is deprecated",
+            "this crate has been deprecated",
+        ]
+        .iter()
+        .any(|prefix| d.starts_with(prefix))
+    });
+    let in_keywords = keywords
+        .into_iter()
+        .any(|k| k.eq_ignore_ascii_case("deprecated"));
+    (in_description || in_keywords).then(|| Notice {
+        kind: NoticeKind::Deprecated,
+        id: None,
+        message: description.unwrap_or("crate is deprecated").to_string(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn query_unmaintained_advisories() -> crate::Result<()> {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server
+            .mock("POST", "/v1/query")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "package": {"name": "ansi_term", "ecosystem": "crates.io"},
+                "version": "0.12.1",
+            })))
+            .with_body(
+                r#"{"vulns": [
+                    {
+                        "id": "RUSTSEC-2021-0139",
+                        "summary": "ansi_term is Unmaintained",
+                        "database_specific": {"informational": "unmaintained"}
+                    },
+                    {
+                        "id": "RUSTSEC-2000-0000",
+                        "summary": "Some vulnerability",
+                        "database_specific": {}
+                    }
+                ]}"#,
+            )
+            .create_async()
+            .await;
+        let notices = rustsec_notices(
+            &reqwest::Client::new(),
+            &server.url(),
+            "ansi_term",
+            Some(&semver::Version::new(0, 12, 1)),
+        )
+        .await?;
+        mock.assert_async().await;
+        assert_eq!(
+            notices,
+            vec![Notice {
+                kind: NoticeKind::Unmaintained,
+                id: Some("RUSTSEC-2021-0139".to_string()),
+                message: "ansi_term is Unmaintained".to_string(),
+            }]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn detect_deprecation() {
+        assert!(deprecation_notice(Some("DEPRECATED: use other_crate"), []).is_some());
+        assert!(deprecation_notice(Some("[Deprecated] A parser"), []).is_some());
+        assert!(deprecation_notice(Some("This crate is deprecated, use bar"), []).is_some());
+        assert!(deprecation_notice(Some("Lints for deprecated attributes"), ["parsing"]).is_none());
+        assert!(deprecation_notice(Some("A useful crate"), ["deprecated"]).is_some());
+        assert!(deprecation_notice(Some("A useful crate"), ["parsing"]).is_none());
+    }
+}
@@ -0,0 +1,299 @@
+use std::collections::BTreeMap;
+
+use biblatex::ChunksExt;
+use serde::{Deserialize, Serialize};
+
+use crate::{BibLaTeX, BibLaTeXCratesIO};
+
+/// Combines the citations of many crates into a single bibliography without duplicates.
+///
+/// Entries are considered identical if they share the same DOI or if they share the same url and
+/// title while their versions and DOIs do not contradict each other.
+/// Works which merely share a title or crates of a workspace which share a repository are kept
+/// apart.
+/// The key of the first entry is kept while the keys of its duplicates are recorded as aliases in
+/// its `ids` field such that existing `\cite` commands remain valid.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct AggregatedBibliography {
+    /// All distinct entries sorted by their key
+    pub entries: BTreeMap<String, biblatex::Entry>,
+    /// Maps the name of every crate to the key by which it should be cited
+    pub keys: BTreeMap<String, String>,
+}
+
+fn normalize_title(title: &str) -> String {
+    title
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(|c| c.to_lowercase())
+        .collect()
+}
+
+/// The fields which identify the cited work
+struct Identity {
+    doi: Option<String>,
+    url: Option<String>,
+    title: Option<String>,
+    version: Option<String>,
+}
+
+impl Identity {
+    fn of(entry: &biblatex::Entry) -> Self {
+        Identity {
+            doi: entry
+                .doi()
+                .ok()
+                .map(|doi| crate::normalize_doi(&doi).to_lowercase()),
+            url: entry.url().ok().map(|url| crate::normalize_url(&url)),
+            title: entry
+                .title()
+                .ok()
+                .map(|t| normalize_title(&t.format_verbatim()))
+                .filter(|t| !t.is_empty()),
+            version: entry
+                .get("version")
+                .map(|v| v.format_verbatim())
+                .filter(|v| !v.is_empty()),
+        }
+    }
+
+    fn same_work(&self, other: &Identity) -> bool {
+        let conflicts =
+            |a: &Option<String>, b: &Option<String>| a.is_some() && b.is_some() && a != b;
+        if let (Some(a), Some(b)) = (&self.doi, &other.doi) {
+            return a == b;
+        }
+        self.url.is_some()
+            && self.url == other.url
+            && self.title.is_some()
+            && self.title == other.title
+            && !conflicts(&self.version, &other.version)
+    }
+}
+
+fn aliases(entry: &biblatex::Entry) -> Vec<String> {
+    entry.get_as::<Vec<String>>("ids").unwrap_or_default()
+}
+
+/// Converts a single result into the entries which cite it
+pub fn result_entries(result: &BibLaTeX) -> crate::Result<Vec<biblatex::Entry>> {
+    match result {
+        BibLaTeX::Plain(plain) => Ok(plain.selected_entries().into_iter().cloned().collect()),
+        BibLaTeX::CITATIONCFF(cff) => {
+            let entry = BibLaTeXCratesIO::from_citation_cff(cff)
+                .map_err(|e| crate::Err::FiletypeUnsupported(format!("{e}")))?;
+            Ok(vec![entry.to_entry()])
+        }
+        BibLaTeX::CratesIO(b) | BibLaTeX::Repository(b) | BibLaTeX::Zenodo(b) => {
+            Ok(vec![b.to_entry()])
+        }
+        BibLaTeX::SoftwareMetadata(m) => Ok(vec![m.to_entry()]),
+    }
+}
+
+impl AggregatedBibliography {
+    fn find_duplicate(&self, entry: &biblatex::Entry) -> Option<String> {
+        let identity = Identity::of(entry);
+        self.entries
+            .iter()
+            .find(|(_, e)| Identity::of(e).same_work(&identity))
+            .map(|(key, _)| key.clone())
+    }
+
+    fn unique_key(&self, key: &str) -> String {
+        let taken = |k: &str| {
+            self.entries.contains_key(k)
+                || self
+                    .entries
+                    .values()
+                    .any(|e| aliases(e).contains(&k.to_string()))
+        };
+        if !taken(key) {
+            return key.to_string();
+        }
+        (2..)
+            .map(|n| format!("{key}_{n}"))
+            .find(|k| !taken(k))
+            .unwrap()
+    }
+
+    /// Adds an entry cited by the given crate and returns the key under which it is stored.
+    ///
+    /// If the entry is a duplicate, missing fields of the existing entry are filled and the key of
+    /// the new entry is added as an alias.
+    pub fn add_entry(&mut self, crate_name: &str, mut entry: biblatex::Entry) -> String {
+        let key = match self.find_duplicate(&entry) {
+            Some(key) => {
+                let existing = self.entries.get_mut(&key).unwrap();
+                for (field, value) in entry.fields.iter() {
+                    if !existing.fields.contains_key(field) && field != "ids" {
+                        existing.fields.insert(field.clone(), value.clone());
+                    }
+                }
+                let mut ids = aliases(existing);
+                for alias in std::iter::once(entry.key.clone()).chain(aliases(&entry)) {
+                    if alias != key && !ids.contains(&alias) {
+                        ids.push(alias);
+                    }
+                }
+                if !ids.is_empty() {
+                    existing.set_as("ids", &ids);
+                }
+                key
+            }
+            None => {
+                let key = self.unique_key(&entry.key);
+                entry.key = key.clone();
+                self.entries.insert(key.clone(), entry);
+                key
+            }
+        };
+        self.keys
+            .entry(crate_name.to_string())
+            .or_insert(key.clone());
+        key
+    }
+
+    /// Adds the first of the results which were obtained for the given crate.
+    ///
+    /// The results are expected to be sorted by priority as done by [crate::get_biblatex].
+    /// Returns the key by which the crate should be cited.
+    pub fn add(&mut self, crate_name: &str, results: &[BibLaTeX]) -> crate::Result<Option<String>> {
+        let Some(result) = results.first() else {
+            return Ok(None);
+        };
+        let mut key = None;
+        for entry in result_entries(result)? {
+            let k = self.add_entry(crate_name, entry);
+            key.get_or_insert(k);
+        }
+        Ok(key)
+    }
+
+    /// The deduplicated bibliography in a stable order
+    pub fn bibliography(&self) -> biblatex::Bibliography {
+        let mut bibliography = biblatex::Bibliography::new();
+        for entry in self.entries.values() {
+            bibliography.insert(entry.clone());
+        }
+        bibliography
+    }
+
+    /// Defines the mapping from crates to keys as LaTeX macros.
+    ///
+    /// Crates can then be cited by their name via `\citecrate{nalgebra-sparse}`.
+    pub fn to_latex_macros(&self) -> String {
+        let mut out = String::from("\\makeatletter\n");
+        for (name, key) in self.keys.iter() {
+            out.push_str(&format!("\\@namedef{{crate2bib@{name}}}{{{key}}}\n"));
+        }
+        out.push_str("\\makeatother\n");
+        out.push_str(
+            "\\newcommand{\\citecrate}[1]\
+            {\\expandafter\\cite\\expandafter{\\csname crate2bib@#1\\endcsname}}\n",
+        );
+        out
+    }
+}
+
+impl std::fmt::Display for AggregatedBibliography {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&crate::entries_to_biblatex_string(
+            self.entries.values(),
+            crate::Encoding::default(),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(text: &str) -> biblatex::Entry {
+        biblatex::Bibliography::parse(text)
+            .unwrap()
+            .into_vec()
+            .remove(0)
+    }
+
+    #[test]
+    fn deduplicate_shared_citations() {
+        let mut aggregated = AggregatedBibliography::default();
+        let paper = "@article{Crozet2023, title = {nalgebra: linear algebra}, \
+            doi = {10.1234/nalgebra}}";
+        let sparse = "@software{nalgebra_sparse, title = {nalgebra-sparse}, \
+            doi = {https://doi.org/10.1234/NALGEBRA}, version = {0.9.0}}";
+        assert_eq!(aggregated.add_entry("nalgebra", entry(paper)), "Crozet2023");
+        assert_eq!(
+            aggregated.add_entry("nalgebra-sparse", entry(sparse)),
+            "Crozet2023"
+        );
+        let workspace = "@software{Pleyer2025, title = {cellular\\_raza-core}, \
+            url = {https://github.com/jonaspleyer/cellular_raza}, version = {0.2.4}}";
+        let member = "@software{Pleyer2025b, title = {cellular\\_raza-building-blocks}, \
+            url = {https://github.com/jonaspleyer/cellular_raza/}, version = {0.2.4}}";
+        let same = "@software{cellular_raza_core, title = {cellular\\_raza-core}, \
+            url = {https://github.com/jonaspleyer/cellular_raza/}}";
+        let other = "@software{Pleyer2025, title = {crate2bib}}";
+        aggregated.add_entry("cellular_raza-core", entry(workspace));
+        assert_eq!(
+            aggregated.add_entry("cellular_raza-building-blocks", entry(member)),
+            "Pleyer2025b"
+        );
+        assert_eq!(
+            aggregated.add_entry("cellular_raza", entry(same)),
+            "Pleyer2025"
+        );
+        assert_eq!(
+            aggregated.add_entry("crate2bib", entry(other)),
+            "Pleyer2025_2"
+        );
+        // Distinct works which share a title are kept apart
+        let paper = "@article{Doe2020, title = {nalgebra: Linear Algebra}, doi = {10.1/other}}";
+        assert_eq!(aggregated.add_entry("other", entry(paper)), "Doe2020");
+
+        assert_eq!(aggregated.entries.len(), 5);
+        let shared = &aggregated.entries["Crozet2023"];
+        assert_eq!(aliases(shared), vec!["nalgebra_sparse"]);
+        assert_eq!(shared.get_as::<String>("version").unwrap(), "0.9.0");
+        assert_eq!(
+            aliases(&aggregated.entries["Pleyer2025"]),
+            vec!["cellular_raza_core"]
+        );
+        assert_eq!(
+            aggregated.keys,
+            BTreeMap::from(
+                [
+                    ("nalgebra", "Crozet2023"),
+                    ("nalgebra-sparse", "Crozet2023"),
+                    ("cellular_raza-core", "Pleyer2025"),
+                    ("cellular_raza-building-blocks", "Pleyer2025b"),
+                    ("cellular_raza", "Pleyer2025"),
+                    ("crate2bib", "Pleyer2025_2"),
+                    ("other", "Doe2020"),
+                ]
+                .map(|(a, b)| (a.to_string(), b.to_string()))
+            )
+        );
+
+        let output = aggregated.to_string();
+        let keys: Vec<_> = biblatex::Bibliography::parse(&output)
+            .unwrap()
+            .iter()
+            .map(|e| e.key.clone())
+            .collect();
+        assert_eq!(
+            keys,
+            vec![
+                "Crozet2023",
+                "Doe2020",
+                "Pleyer2025",
+                "Pleyer2025_2",
+                "Pleyer2025b"
+            ]
+        );
+        assert!(aggregated
+            .to_latex_macros()
+            .contains("\\@namedef{crate2bib@nalgebra-sparse}{Crozet2023}"));
+    }
+}
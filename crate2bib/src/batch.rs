@@ -0,0 +1,157 @@
+use futures::StreamExt;
+use serde::{Deserialize, Serialize};
+
+use crate::BibLaTeX;
+
+/// A crate which should be cited in batch mode given as `name` or `name@requirement`
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct CrateRequest {
+    /// Name of the crate
+    pub name: String,
+    /// Version requirement such as `1.0` or `=0.2.4`
+    pub version: Option<String>,
+}
+
+impl std::str::FromStr for CrateRequest {
+    type Err = crate::Err;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (name, version) = match s.split_once('@') {
+            Some((name, version)) => (name.trim(), Some(version.trim())),
+            None => (s, None),
+        };
+        let valid = !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid {
+            return Err(crate::Err::InvalidValue(format!(
+                "{s} is not of the form name or name@requirement"
+            )));
+        }
+        Ok(CrateRequest {
+            name: name.to_string(),
+            version: version.filter(|v| !v.is_empty()).map(|v| v.to_string()),
+        })
+    }
+}
+
+impl std::fmt::Display for CrateRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.version {
+            Some(version) => write!(f, "{}@{version}", self.name),
+            None => f.write_str(&self.name),
+        }
+    }
+}
+
+/// Parses a list of crates with one `name[@requirement]` per line.
+///
+/// Empty lines and comments starting with `#` are ignored.
+pub fn parse_crate_list(text: &str) -> crate::Result<Vec<CrateRequest>> {
+    text.lines()
+        .map(|line| line.split('#').next().unwrap_or_default().trim())
+        .filter(|line| !line.is_empty())
+        .map(|line| line.parse())
+        .collect()
+}
+
+/// Obtain BibLaTeX entries for many crates concurrently.
+///
+/// At most `concurrency` crates are processed at the same time while all requests to
+/// [crates.io](https://crates.io) share a single rate limit (see [crate::CratesIoClient]).
+/// The `progress` callback is invoked whenever a crate is finished.
+/// Failures of individual crates are reported alongside the crate and do not abort the
+/// remaining crates.
+/// The results are returned in the order of the requests.
+pub async fn get_biblatex_batch(
+    requests: &[CrateRequest],
+    options: &crate::ResolveOptions,
+    user_agent: Option<&str>,
+    filenames: Vec<&str>,
+    concurrency: usize,
+    mut progress: impl FnMut(&CrateRequest, &crate::Result<Vec<BibLaTeX>>),
+) -> crate::Result<Vec<(CrateRequest, crate::Result<Vec<BibLaTeX>>)>> {
+    let client = crate::build_client(user_agent)?;
+    let crates_io_client = crate::build_crates_io_client(&client);
+    let mut stream = futures::stream::iter(requests.iter().enumerate())
+        .map(|(n, request)| {
+            let client = &client;
+            let crates_io_client = &crates_io_client;
+            let filenames = filenames.clone();
+            async move {
+                #[cfg(feature = "log")]
+                log::trace!("Obtaining entries for {request}");
+                let r = crate::get_biblatex_with_clients(
+                    &request.name,
+                    request.version.as_deref(),
+                    options,
+                    client,
+                    crates_io_client,
+                    None,
+                    filenames,
+                )
+                .await;
+                (n, r)
+            }
+        })
+        .buffer_unordered(concurrency.max(1));
+    let mut results: Vec<_> = requests.iter().map(|r| (r.clone(), None)).collect();
+    while let Some((n, r)) = stream.next().await {
+        progress(&requests[n], &r);
+        results[n].1 = Some(r);
+    }
+    Ok(results
+        .into_iter()
+        .filter_map(|(request, r)| Some((request, r?)))
+        .collect())
+}
+
+/// Combines the preferred result of every successfully cited crate into a single deduplicated
+/// bibliography (see [crate::AggregatedBibliography])
+pub fn batch_bibliography(
+    results: &[(CrateRequest, crate::Result<Vec<BibLaTeX>>)],
+) -> crate::Result<crate::AggregatedBibliography> {
+    let mut bibliography = crate::AggregatedBibliography::default();
+    for (request, result) in results {
+        if let Ok(result) = result {
+            bibliography.add(&request.name, result)?;
+        }
+    }
+    Ok(bibliography)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_crate_requests() -> crate::Result<()> {
+        let list = "serde\n# comment\n\nrand@0.8 # pinned\n cellular_raza@=0.2.4\n";
+        let requests = parse_crate_list(list)?;
+        assert_eq!(
+            requests,
+            vec![
+                CrateRequest {
+                    name: "serde".to_string(),
+                    version: None,
+                },
+                CrateRequest {
+                    name: "rand".to_string(),
+                    version: Some("0.8".to_string()),
+                },
+                CrateRequest {
+                    name: "cellular_raza".to_string(),
+                    version: Some("=0.2.4".to_string()),
+                },
+            ]
+        );
+        assert_eq!(requests[1].to_string(), "rand@0.8");
+        assert!(matches!(
+            "not a crate".parse::<CrateRequest>(),
+            Err(crate::Err::InvalidValue(_))
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,143 @@
+use chrono::Datelike;
+use serde_yaml::{Mapping, Value};
+
+use crate::{BibLaTeX, BibLaTeXCratesIO};
+
+/// Parses the contents of a `CITATION.cff` file without resolving its DOI
+pub fn parse_citation_cff(text: &str) -> crate::Result<BibLaTeX> {
+    Ok(BibLaTeX::CITATIONCFF(citeworks_cff::from_str(text)?))
+}
+
+fn string(value: impl Into<String>) -> Value {
+    Value::String(value.into())
+}
+
+/// Splits titles of the form `{name}: description` into the name and description
+fn split_title(title: &str) -> (String, Option<String>) {
+    if let Some(rest) = title.strip_prefix('{') {
+        if let Some((name, description)) = rest.split_once('}') {
+            let description = description.trim_start_matches(':').trim();
+            return (
+                name.to_string(),
+                (!description.is_empty()).then(|| description.to_string()),
+            );
+        }
+    }
+    (title.replace(['{', '}'], ""), None)
+}
+
+impl BibLaTeXCratesIO {
+    /// Writes the entry as the contents of a
+    /// [`CITATION.cff`](https://citation-file-format.github.io) file.
+    ///
+    /// Authors are split into their given and family names at the last space while authors
+    /// consisting of a single word are written as entities.
+    pub fn to_citation_cff(&self) -> crate::Result<String> {
+        let mut map = Mapping::new();
+        let mut insert = |key: &str, value: Option<Value>| {
+            if let Some(value) = value {
+                map.insert(string(key), value);
+            }
+        };
+        let (title, description) = split_title(&self.title);
+        insert("cff-version", Some(string("1.2.0")));
+        insert(
+            "message",
+            Some(string("If you use this software, please cite it as below.")),
+        );
+        insert(
+            "type",
+            Some(string(match self.work_type.as_str() {
+                "dataset" => "dataset",
+                _ => "software",
+            })),
+        );
+        insert("title", Some(string(title)));
+        insert("abstract", description.map(string));
+        let authors: Vec<_> = self
+            .author
+            .iter()
+            .map(|p| {
+                let mut author = Mapping::new();
+                if p.given_name.is_empty() {
+                    author.insert(string("name"), string(&p.name));
+                } else {
+                    author.insert(string("given-names"), string(&p.given_name));
+                    if !p.prefix.is_empty() {
+                        author.insert(string("name-particle"), string(&p.prefix));
+                    }
+                    author.insert(string("family-names"), string(&p.name));
+                    if !p.suffix.is_empty() {
+                        author.insert(string("name-suffix"), string(&p.suffix));
+                    }
+                }
+                Value::Mapping(author)
+            })
+            .collect();
+        insert("authors", Some(Value::Sequence(authors)));
+        insert(
+            "version",
+            self.version.as_ref().map(|v| string(v.to_string())),
+        );
+        insert(
+            "date-released",
+            self.date
+                .map(|d| string(format!("{:04}-{:02}-{:02}", d.year(), d.month(), d.day()))),
+        );
+        insert("license", self.license.clone().map(string));
+        insert("repository-code", self.url.clone().map(string));
+        insert("doi", self.doi.clone().map(string));
+        insert(
+            "keywords",
+            (!self.keywords.is_empty())
+                .then(|| Value::Sequence(self.keywords.iter().map(string).collect())),
+        );
+        Ok(serde_yaml::to_string(&map)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn write_citation_cff() -> crate::Result<()> {
+        let manifest = r#"
+[package]
+name = "cellular_raza"
+version = "0.2.4"
+authors = ["Jonas Pleyer <jonas.pleyer@fdm.uni-freiburg.de>", "Christian Fleck"]
+description = "Cellular Agent-based Modeling from a Clean Slate"
+license = "GPL-2.0"
+repository = "https://github.com/jonaspleyer/cellular_raza"
+"#;
+        let entry = crate::manifest_to_biblatex(manifest, None)?;
+        let cff = entry.to_citation_cff()?;
+        let expected = "\
+cff-version: 1.2.0
+message: If you use this software, please cite it as below.
+type: software
+title: cellular_raza
+abstract: Cellular Agent-based Modeling from a Clean Slate
+authors:
+- given-names: Jonas
+  family-names: Pleyer
+- given-names: Christian
+  family-names: Fleck
+version: 0.2.4
+license: GPL-2.0
+repository-code: https://github.com/jonaspleyer/cellular_raza
+";
+        assert_eq!(cff, expected);
+
+        // The written file can be read again
+        let BibLaTeX::CITATIONCFF(parsed) = parse_citation_cff(&cff)? else {
+            panic!("expected a CITATION.cff result");
+        };
+        let back = BibLaTeXCratesIO::from_citation_cff(&parsed).unwrap();
+        assert_eq!(back.author_names(), "Jonas Pleyer, Christian Fleck");
+        assert_eq!(back.title, entry.title);
+        assert_eq!(back.version, entry.version);
+        Ok(())
+    }
+}
@@ -6,7 +6,7 @@ use serde::{Deserialize, Serialize};
 
 /// A fully specified BibLaTeX entry generated from a crate hostedn on
 /// [crates.io](https://crates.io)
-#[derive(Clone, Debug, Deserialize, Serialize)]
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
 #[cfg_attr(feature = "pyo3", pyclass)]
 pub struct BibLaTeXCratesIO {
     /// BibLaTeX citation key which can be used in LaTeX `\cite{key}`.
@@ -14,17 +14,42 @@ pub struct BibLaTeXCratesIO {
     /// One of BibLaTeX's types. This is usually `software` in our case
     pub work_type: String,
     /// All authors of the crate.
-    pub author: String,
+    ///
+    /// Organizations only have a [biblatex::Person::name] (see [crate::organization]).
+    #[serde(with = "crate::serde_persons")]
+    pub author: Vec<biblatex::Person>,
     /// The title of the crate is a combination of the name, version and description of the crate
     pub title: String,
     /// Contains the repository where the crate is hosted
     pub url: Option<String>,
+    /// The [DOI](https://www.doi.org/) under which the software was published
+    pub doi: Option<String>,
     /// The license under which the software is distributed
     pub license: Option<String>,
     /// Version which was automatically found by [semver]
     pub version: Option<semver::Version>,
     /// The time at which this version was published
     pub date: Option<chrono::DateTime<chrono::Utc>>,
+    /// Indicates if the cited version was yanked
+    #[serde(default)]
+    pub yanked: bool,
+    /// Notices which mark the crate as deprecated or unmaintained
+    #[serde(default)]
+    pub notices: Vec<crate::Notice>,
+    /// Keywords describing the software
+    #[serde(default)]
+    pub keywords: Vec<String>,
+    /// [Software Heritage persistent identifier](https://www.softwareheritage.org) of the cited
+    /// revision (see [crate::add_swhid])
+    #[serde(default)]
+    pub swhid: Option<String>,
+    /// Additional remarks such as why a concept DOI is cited instead of a version DOI
+    #[serde(default)]
+    pub note: Option<String>,
+    /// Paths of all citation files which were found in the repository
+    /// (see [crate::github_search_files])
+    #[serde(default)]
+    pub citation_files: Vec<String>,
 }
 
 impl BibLaTeXCratesIO {
@@ -70,7 +95,7 @@ impl BibLaTeXCratesIO {
         );
         #[cfg(feature = "log")]
         log::trace!("Formatting Key");
-        let key = format!(
+        let key = crate::sanitize_key(&format!(
             "{}{}",
             authors
                 .first()
@@ -84,7 +109,7 @@ impl BibLaTeXCratesIO {
             date_released
                 .map(|d| format!("{:4}", d.year))
                 .unwrap_or("".to_owned())
-        );
+        ));
         #[cfg(feature = "log")]
         log::trace!("Formatting Authors");
         let author = authors
@@ -100,27 +125,23 @@ impl BibLaTeXCratesIO {
                         name_suffix,
                         affiliation,
                         meta,
-                    }) => format!(
-                        "{}{}{}{}",
-                        given_names.map(|x| format!("{x} ")).unwrap_or_default(),
-                        name_particle.map(|x| format!("{x} ")).unwrap_or_default(),
-                        family_names.map(|x| format!("{x} ")).unwrap_or_default(),
-                        name_suffix.unwrap_or_default(),
-                    )
-                    .trim_end()
-                    .to_string(),
+                    }) => biblatex::Person {
+                        name: family_names.unwrap_or_default(),
+                        given_name: given_names.unwrap_or_default(),
+                        prefix: name_particle.unwrap_or_default(),
+                        suffix: name_suffix.unwrap_or_default(),
+                    },
                     #[allow(unused)]
                     Entity(citeworks_cff::names::EntityName {
                         name,
                         date_start,
                         date_end,
                         meta,
-                    }) => name.unwrap_or_default(),
-                    Anonymous => "Anonymous".to_string(),
+                    }) => crate::organization(&name.unwrap_or_default()),
+                    Anonymous => crate::organization("Anonymous"),
                 }
             })
-            .reduce(|acc, x| format!("{acc}, {x}"))
-            .unwrap_or_default();
+            .collect();
         #[cfg(feature = "log")]
         log::trace!("Finishing Conversion");
         Ok(Self {
@@ -131,7 +152,7 @@ impl BibLaTeXCratesIO {
                 None => "software",
             }
             .to_string(),
-            author, // authors.into_iter().map(|a| format!("{a}")),
+            author,
             title: format!(
                 "{{{title}}}{}",
                 abstract_text.map_or_else(|| "".to_string(), |x| format!(": {x}"))
@@ -140,6 +161,10 @@ impl BibLaTeXCratesIO {
                 .map(|url| format!("{url}"))
                 .or(repository_code.map(|url| format!("{url}")))
                 .or(repository_artifact.map(|url| format!("{url}"))),
+            doi: doi.or(identifiers.into_iter().find_map(|i| match i {
+                citeworks_cff::identifiers::Identifier::Doi { value, .. } => Some(value),
+                _ => None,
+            })),
             license: match license {
                 Some(citeworks_cff::License::Single(l)) => Some(format!("{l}")),
                 Some(citeworks_cff::License::AnyOf(ll)) => {
@@ -162,39 +187,24 @@ impl BibLaTeXCratesIO {
             },
             version,
             date,
+            ..Default::default()
         })
     }
 }
 
+impl BibLaTeXCratesIO {
+    /// Formats the entry while escaping all fields with the given [crate::Encoding] (see
+    /// [crate::entry_to_biblatex_string]).
+    pub fn to_biblatex_string(&self, encoding: crate::Encoding) -> String {
+        crate::entry_to_biblatex_string(&self.to_entry(), encoding)
+    }
+}
+
 impl std::fmt::Display for BibLaTeXCratesIO {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         #[cfg(feature = "log")]
         log::trace!("Formatting BibLaTeXCratesIO");
-        // Writes the biblatex entry
-        writeln!(f, "@{} {{{},", self.work_type, self.key)?;
-        writeln!(f, "    author = {{{}}},", self.author)?;
-        writeln!(f, "    title = {{{}}},", self.title)?;
-        if let Some(u) = &self.url {
-            writeln!(f, "    url = {{{u}}},")?;
-        };
-        if let Some(date) = self.date {
-            writeln!(
-                f,
-                "    date = {{{:4.0}-{:02}-{:02}}},",
-                date.year(),
-                date.month(),
-                date.day(),
-            )?;
-        }
-        if let Some(version) = &self.version {
-            writeln!(f, "    version = {{{version}}},")?;
-        }
-        if let Some(license) = &self.license {
-            writeln!(f, "    license = {{{license}}},")?;
-        }
-        // Closes the entry
-        write!(f, "}}")?;
-        Ok(())
+        f.write_str(&self.to_biblatex_string(crate::Encoding::default()))
     }
 }
 
@@ -208,38 +218,54 @@ pub async fn generate_biblatex_crates_io(
     version: Option<&str>,
     client: &crates_io_api::AsyncClient,
 ) -> crate::Result<BibLaTeXCratesIO> {
+    Ok(generate_biblatex_crates_io_with_options(
+        crate_name,
+        version,
+        &crate::ResolveOptions::default(),
+        client,
+    )
+    .await?
+    .0)
+}
+
+/// Returns a [BibLaTeXCratesIO] entry for the searched crate together with the
+/// [crate::VersionResolution] which explains how the version was selected.
+///
+/// The `version` may be any requirement which is valid in `Cargo.toml` (see
+/// [crate::VersionRequirement]).
+pub async fn generate_biblatex_crates_io_with_options(
+    crate_name: &str,
+    version: Option<&str>,
+    options: &crate::ResolveOptions,
+    client: &crates_io_api::AsyncClient,
+) -> crate::Result<(BibLaTeXCratesIO, crate::VersionResolution)> {
     #[cfg(feature = "log")]
     log::trace!("Obtaining Crate Information");
     let info = client.get_crate(crate_name).await?;
-    #[cfg(feature = "log")]
-    log::trace!("Filter versions");
-    let mut obtained_versions = info
+    let candidates: Vec<_> = info
         .versions
         .iter()
-        .enumerate()
-        .filter_map(|(n, x)| semver::Version::parse(&x.num).ok().map(|y| (n, y)))
-        .collect::<Vec<_>>();
-    obtained_versions.sort_by_key(|x| x.1.clone());
-    obtained_versions.reverse();
+        .map(|x| crate::VersionCandidate {
+            num: x.num.clone(),
+            yanked: x.yanked,
+        })
+        .collect();
+    let resolution = crate::resolve_version(crate_name, &candidates, version, options)?;
+    let found_version = info.versions[resolution.index].clone();
+    let found_version_semver = resolution.version.clone();
 
-    let (index, found_version_semver) = if let Some(version) = version {
-        let version = semver::Comparator::parse(version)?;
-        obtained_versions
-            .into_iter()
-            .find(|x| version.matches(&x.1))
-    } else {
-        obtained_versions.first().cloned()
-    }
-    .ok_or(crate::NotFoundError(
-        version.map_or(format!("Could not find crate {crate_name}"), |x| {
-            format!("Could not find version {x} for crate {crate_name}")
-        }),
-    ))?;
-    let found_version = info.versions[index].clone();
+    #[cfg(feature = "log")]
+    log::trace!("Checking for deprecation");
+    let notices = crate::deprecation_notice(
+        info.crate_data.description.as_deref(),
+        info.keywords.iter().map(|k| k.keyword.as_str()),
+    )
+    .into_iter()
+    .collect();
 
     #[cfg(feature = "log")]
     log::trace!("Bundling Information into BibLaTeXCratesIO");
-    Ok(BibLaTeXCratesIO {
+    let entry = BibLaTeXCratesIO {
         key: format!(
             "{}{}",
             found_version
@@ -254,7 +280,9 @@ pub async fn generate_biblatex_crates_io(
         work_type: "software".to_string(),
         author: found_version
             .published_by
-            .map_or_else(|| "".to_owned(), |x| x.name.unwrap_or(x.login)),
+            .map(|x| crate::person_from_name(&x.name.unwrap_or(x.login)))
+            .into_iter()
+            .collect(),
         title: info
             .crate_data
             .description
@@ -262,10 +290,30 @@ pub async fn generate_biblatex_crates_io(
                 format!("{{{}}}: {}", crate_name, x)
             }),
         url: info.crate_data.repository,
+        doi: None,
         license: found_version.license,
         version: Some(found_version_semver),
         date: Some(found_version.updated_at),
-    })
+        yanked: found_version.yanked,
+        notices,
+        ..Default::default()
+    };
+    Ok((entry, resolution))
+}
+
+/// Builds the [reqwest::Client] which is used for all requests.
+pub fn build_client(user_agent: Option<&str>) -> crate::Result<reqwest::Client> {
+    use reqwest::header::*;
+    #[cfg(feature = "log")]
+    log::trace!("Prepare Headers and Client");
+    let mut headers = HeaderMap::new();
+    if let Some(ua) = user_agent {
+        headers.insert(USER_AGENT, HeaderValue::from_str(ua)?);
+    }
+
+    Ok(reqwest::Client::builder()
+        .default_headers(headers)
+        .build()?)
 }
 
 /// Obtain multiple BibLaTeX entries from various sources such as crates.io, github and doi.org
@@ -276,37 +324,241 @@ pub async fn get_biblatex(
     branch_name: Option<&str>,
     filenames: Vec<&str>,
 ) -> crate::Result<Vec<crate::BibLaTeX>> {
-    use crates_io_api::AsyncClient;
-    use reqwest::header::*;
-    #[cfg(feature = "log")]
-    log::trace!("Prepare Headers and Client");
-    let mut headers = HeaderMap::new();
-    if let Some(ua) = user_agent {
-        headers.insert(USER_AGENT, HeaderValue::from_str(ua)?);
+    get_biblatex_with_options(
+        crate_name,
+        version,
+        &crate::ResolveOptions::default(),
+        user_agent,
+        branch_name,
+        filenames,
+    )
+    .await
+}
+
+/// Works identical to [get_biblatex] but selects the version according to the given
+/// [crate::ResolveOptions].
+///
+/// If [crate::ResolveOptions::advisories] is set, RustSec advisories which mark the crate as
+/// unmaintained are attached to the entry (see [crate::rustsec_notices]).
+pub async fn get_biblatex_with_options(
+    crate_name: &str,
+    version: Option<&str>,
+    options: &crate::ResolveOptions,
+    user_agent: Option<&str>,
+    branch_name: Option<&str>,
+    filenames: Vec<&str>,
+) -> crate::Result<Vec<crate::BibLaTeX>> {
+    let client = build_client(user_agent)?;
+    let crates_io_client = build_crates_io_client(&client);
+    get_biblatex_with_clients(
+        crate_name,
+        version,
+        options,
+        &client,
+        &crates_io_client,
+        branch_name,
+        filenames,
+    )
+    .await
+}
+
+/// Client for [crates.io](https://crates.io) which respects its rate limit of one request per
+/// second.
+///
+/// Requests to the API and for READMEs (see [crate::crates_io_readme]) share the rate limit and so
+/// do clones of the client.
+#[derive(Clone)]
+pub struct CratesIoClient {
+    api: crates_io_api::AsyncClient,
+    http: reqwest::Client,
+    rate_limit: web_time::Duration,
+    last_request: std::sync::Arc<futures::lock::Mutex<Option<web_time::Instant>>>,
+}
+
+impl CratesIoClient {
+    /// Waits until the rate limit allows the next request and executes it.
+    ///
+    /// The delay does not depend on a specific async runtime such that it also works in the
+    /// browser.
+    pub(crate) async fn throttle<T>(&self, request: impl std::future::Future<Output = T>) -> T {
+        let mut last_request = self.last_request.lock().await;
+        if let Some(elapsed) = last_request.map(|t| t.elapsed()) {
+            if elapsed < self.rate_limit {
+                futures_timer::Delay::new(self.rate_limit - elapsed).await;
+            }
+        }
+        let result = request.await;
+        *last_request = Some(web_time::Instant::now());
+        result
     }
 
-    let client1 = reqwest::Client::builder()
-        .default_headers(headers)
-        .build()?;
-    let client =
-        AsyncClient::with_http_client(client1.clone(), web_time::Duration::from_millis(1000));
-    let r1 = generate_biblatex_crates_io(crate_name, version, &client).await?;
-    let url = r1.url.clone();
+    /// The client of the crates.io API whose requests are not throttled by itself.
+    ///
+    /// Requests have to be wrapped in [CratesIoClient::throttle].
+    pub(crate) fn api(&self) -> &crates_io_api::AsyncClient {
+        &self.api
+    }
+
+    /// The underlying http client
+    pub(crate) fn http(&self) -> &reqwest::Client {
+        &self.http
+    }
+}
+
+/// Builds the [CratesIoClient] which respects the crates.io rate limit of one request per second
+pub fn build_crates_io_client(client: &reqwest::Client) -> CratesIoClient {
+    CratesIoClient {
+        // All requests are throttled by the CratesIoClient instead
+        api: crates_io_api::AsyncClient::with_http_client(client.clone(), web_time::Duration::ZERO),
+        http: client.clone(),
+        rate_limit: web_time::Duration::from_millis(1000),
+        last_request: Default::default(),
+    }
+}
+
+/// Works identical to [get_biblatex_with_options] but reuses the given clients
+pub(crate) async fn get_biblatex_with_clients(
+    crate_name: &str,
+    version: Option<&str>,
+    options: &crate::ResolveOptions,
+    client1: &reqwest::Client,
+    client: &CratesIoClient,
+    branch_name: Option<&str>,
+    filenames: Vec<&str>,
+) -> crate::Result<Vec<crate::BibLaTeX>> {
+    let (mut r1, _) = client
+        .throttle(generate_biblatex_crates_io_with_options(
+            crate_name,
+            version,
+            options,
+            client.api(),
+        ))
+        .await?;
+    if options.advisories {
+        #[cfg(feature = "log")]
+        log::trace!("Checking for advisories");
+        match crate::rustsec_notices(client1, crate::OSV_API_URL, crate_name, r1.version.as_ref())
+            .await
+        {
+            Ok(notices) => r1.notices.extend(notices),
+            #[allow(unused)]
+            Err(e) => {
+                #[cfg(feature = "log")]
+                log::warn!("Could not obtain advisories for crate {crate_name}: \"{e}\"");
+            }
+        }
+    }
+    let readme = match &r1.version {
+        Some(v) => crate::crates_io_readme(client, crate_name, v).await.ok(),
+        None => None,
+    };
+    search_repository_of_entry(client1, crate_name, r1, readme, branch_name, filenames).await
+}
+
+/// Obtain multiple BibLaTeX entries for a crate hosted on an alternative [crate::Registry]
+///
+/// Works identical to [get_biblatex_with_options] but uses [crate::generate_biblatex_registry]
+/// instead of
+/// [crates.io](https://crates.io).
+pub async fn get_biblatex_registry(
+    crate_name: &str,
+    version: Option<&str>,
+    options: &crate::ResolveOptions,
+    registry: &crate::Registry,
+    user_agent: Option<&str>,
+    branch_name: Option<&str>,
+    filenames: Vec<&str>,
+) -> crate::Result<Vec<crate::BibLaTeX>> {
+    let client = build_client(user_agent)?;
+    let r1 =
+        crate::generate_biblatex_registry(crate_name, version, options, registry, &client).await?;
+    search_repository_of_entry(&client, crate_name, r1, None, branch_name, filenames).await
+}
+
+/// Searches the repository of the given entry for citation files, archived releases on
+/// [Zenodo](https://zenodo.org) and DOIs in the README (see [crate::discover_dois]) and sorts all
+/// results by priority.
+async fn search_repository_of_entry(
+    client: &reqwest::Client,
+    crate_name: &str,
+    entry: BibLaTeXCratesIO,
+    readme: Option<String>,
+    branch_name: Option<&str>,
+    filenames: Vec<&str>,
+) -> crate::Result<Vec<crate::BibLaTeX>> {
+    let url = entry.url.clone();
+    let version = entry.version.clone();
 
     #[cfg(feature = "log")]
     log::trace!("Obtain entry from crates.io");
-    let mut results = vec![crate::BibLaTeX::CratesIO(r1)];
+    let mut results = vec![crate::BibLaTeX::CratesIO(entry)];
     #[cfg(feature = "log")]
     log::trace!("Obtain other entries");
-    if let Some(u) = url {
-        results
-            .extend(crate::github_search_files(&client1, &u, filenames, branch_name, true).await?);
+    if let Some(u) = &url {
+        if let Some(z) =
+            zenodo_entry(client, &crate::ZenodoQuery::Repository(u), version.as_ref()).await
+        {
+            results.push(crate::BibLaTeX::Zenodo(z));
+        }
+        let (found, files) =
+            crate::github_find_files(client, u, filenames, branch_name, true).await?;
+        if let crate::BibLaTeX::CratesIO(entry) = &mut results[0] {
+            entry.citation_files = files;
+        }
+        results.extend(found);
+        let mut discovered: Vec<_> = readme
+            .iter()
+            .flat_map(|r| crate::find_dois(r))
+            .map(|doi| ("README".to_string(), doi))
+            .collect();
+        match crate::discover_dois(client, u, branch_name).await {
+            Ok(d) => discovered.extend(d),
+            #[allow(unused)]
+            Err(e) => {
+                #[cfg(feature = "log")]
+                log::warn!("Could not search {u} for DOIs: \"{e}\"");
+            }
+        }
+        let candidates = crate::resolve_discovered_dois(client, u, discovered, &results).await;
+        results.extend(candidates);
+    }
+    Ok(rank_results(results, Some(crate_name), url.as_deref()))
+}
+
+/// Selects the entries which cite the crate in all bibliographies (see
+/// [crate::select_cited_entries]) and sorts the results by priority.
+pub(crate) fn rank_results(
+    mut results: Vec<crate::BibLaTeX>,
+    name: Option<&str>,
+    repository: Option<&str>,
+) -> Vec<crate::BibLaTeX> {
+    if let Some(repository) = repository {
+        crate::select_cited_entries(&mut results, name, repository);
     }
     #[cfg(feature = "log")]
     log::trace!("Sort obtained entries by priority");
     results.sort_by_key(|x| u8::MAX - x.priority());
+    results
+}
 
-    Ok(results)
+/// Queries [Zenodo](https://zenodo.org) and only logs errors since archived releases are
+/// optional.
+pub(crate) async fn zenodo_entry(
+    client: &reqwest::Client,
+    query: &crate::ZenodoQuery<'_>,
+    version: Option<&semver::Version>,
+) -> Option<BibLaTeXCratesIO> {
+    #[cfg(feature = "log")]
+    log::trace!("Searching zenodo for archived releases");
+    match crate::zenodo_biblatex(client, crate::ZENODO_URL, query, version).await {
+        Ok(entry) => entry,
+        #[allow(unused)]
+        Err(e) => {
+            #[cfg(feature = "log")]
+            log::warn!("Could not search zenodo: \"{e}\"");
+            None
+        }
+    }
 }
 
 #[cfg(test)]
@@ -325,7 +577,7 @@ mod tests {
         .await?[0]
             .clone();
         let expected = "\
-@software {Tolnay2025,
+@software{Tolnay2025,
     author = {David Tolnay},
     title = {{serde}: A generic serialization/deserialization framework},
     url = {https://github.com/serde-rs/serde},
@@ -351,24 +603,82 @@ mod tests {
             vec!["CITATION.cff"],
         )
         .await?;
-        let bib_entry = &results[0];
-        match bib_entry {
-            BibLaTeX::Plain(_) => (),
-            _ => panic!("Got wrong entry type 1"),
-        }
-        let bib_entry = &results[1];
-        match bib_entry {
-            BibLaTeX::CratesIO(_) => (),
-            _ => panic!("Got wrong return type 2"),
-        }
-        let bib_entry = &results[2];
-        match bib_entry {
-            BibLaTeX::CITATIONCFF(_) => (),
-            _ => panic!("Got wrong return type 3"),
-        }
+        // Archived releases on zenodo may be found in addition so we do not rely on positions
+        assert!(results.iter().any(|r| matches!(r, BibLaTeX::Plain(_))));
+        assert!(results.iter().any(|r| matches!(r, BibLaTeX::CratesIO(_))));
+        assert!(results
+            .iter()
+            .any(|r| matches!(r, BibLaTeX::CITATIONCFF(_))));
         Ok(())
     }
 
+    #[test]
+    fn rank_results_by_crate_name() {
+        let repository = "https://github.com/jonaspleyer/cellular_raza";
+        let entry = BibLaTeXCratesIO {
+            key: "Pleyer2025".to_string(),
+            title: "{cellular_raza}: Cellular Agent-based Modeling from a Clean Slate".to_string(),
+            url: Some(repository.to_string()),
+            ..Default::default()
+        };
+        let bibliography = biblatex::Bibliography::parse(
+            r#"
+@article{Other2020, title = {Related work on agent-based modeling}}
+@misc{cellular_raza_2024, title = {cellular\_raza}}
+"#,
+        )
+        .unwrap();
+        let paper =
+            biblatex::Bibliography::parse("@article{Doe2020, title = {Unrelated}}").unwrap();
+        let results = vec![
+            BibLaTeX::Plain(PlainBibLaTeX {
+                bibliography: paper,
+                repository: repository.to_string(),
+                filename: "README.md".to_string(),
+                doi: Some("10.1000/unrelated".to_string()),
+                selected: vec![],
+                discovered: true,
+            }),
+            BibLaTeX::CratesIO(entry),
+            BibLaTeX::Plain(PlainBibLaTeX {
+                bibliography,
+                repository: repository.to_string(),
+                filename: "citation.bib".to_string(),
+                doi: None,
+                selected: vec![],
+                discovered: false,
+            }),
+        ];
+        let results = rank_results(results, Some("cellular_raza"), Some(repository));
+        match &results[0] {
+            BibLaTeX::Plain(plain) => assert_eq!(plain.selected, vec!["cellular_raza_2024"]),
+            _ => panic!("expected the bibliography of the repository first"),
+        }
+        assert!(matches!(results[1], BibLaTeX::CratesIO(_)));
+        // DOIs found in the README are ranked below all first-party sources
+        assert!(matches!(
+            results[2],
+            BibLaTeX::Plain(PlainBibLaTeX {
+                discovered: true,
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn throttle_shared_between_clones() {
+        let client = CratesIoClient {
+            rate_limit: web_time::Duration::from_millis(50),
+            ..build_crates_io_client(&reqwest::Client::new())
+        };
+        let clone = client.clone();
+        let start = web_time::Instant::now();
+        client.throttle(async {}).await;
+        clone.throttle(async {}).await;
+        client.throttle(async {}).await;
+        assert!(start.elapsed() >= web_time::Duration::from_millis(100));
+    }
+
     #[tokio::test]
     async fn find_crate_without_version() -> crate::Result<()> {
         let results = get_biblatex(
@@ -0,0 +1,237 @@
+use crate::{BibLaTeX, PlainBibLaTeX};
+
+/// Characters which terminate a DOI inside of markdown, HTML or TOML
+const DOI_TERMINATORS: &[char] = &['"', '\'', '<', '>', '`', ']', '}', '|', '\\'];
+
+fn trim_doi(doi: &str) -> Option<String> {
+    let mut doi = doi;
+    loop {
+        let trimmed = doi
+            .trim_end_matches(['.', ',', ';', ':'])
+            .trim_end_matches("/status.svg")
+            .trim_end_matches(".svg");
+        // Only keep closing parentheses which belong to the DOI itself
+        let trimmed = match trimmed.strip_suffix(')') {
+            Some(t) if trimmed.matches(')').count() > trimmed.matches('(').count() => t,
+            _ => trimmed,
+        };
+        if trimmed == doi {
+            break;
+        }
+        doi = trimmed;
+    }
+    let (prefix, suffix) = doi.split_once('/')?;
+    let registrant = prefix.strip_prefix("10.")?;
+    let valid_prefix = (4..=9).contains(&registrant.split('.').next()?.len())
+        && registrant.chars().all(|c| c.is_ascii_digit() || c == '.');
+    (valid_prefix && !suffix.is_empty()).then(|| doi.to_string())
+}
+
+/// Finds all [DOIs](https://www.doi.org/) in a text such as a README.
+///
+/// Besides plain DOIs, this detects links to Zenodo records and DOIs inside of Zenodo and
+/// [JOSS](https://joss.theoj.org) badges.
+/// Every DOI is only returned once in the order in which it appears.
+pub fn find_dois(text: &str) -> Vec<String> {
+    let mut dois: Vec<String> = vec![];
+    let mut push = |doi: Option<String>| {
+        if let Some(doi) = doi {
+            if !dois.iter().any(|d| d.eq_ignore_ascii_case(&doi)) {
+                dois.push(doi);
+            }
+        }
+    };
+    for word in text.split(|c: char| c.is_whitespace() || DOI_TERMINATORS.contains(&c)) {
+        for (n, _) in word.match_indices("10.") {
+            // DOIs start at the beginning of a word or after a separator as in doi.org/10.
+            let at_boundary = word[..n]
+                .chars()
+                .last()
+                .is_none_or(|c| !c.is_ascii_alphanumeric() && c != '.');
+            if at_boundary {
+                push(trim_doi(&word[n..]));
+            }
+        }
+        for pattern in ["zenodo.org/records/", "zenodo.org/record/"] {
+            if let Some((_, id)) = word.split_once(pattern) {
+                let id: String = id.chars().take_while(|c| c.is_ascii_digit()).collect();
+                if !id.is_empty() {
+                    push(Some(format!("10.5281/zenodo.{id}")));
+                }
+            }
+        }
+    }
+    dois
+}
+
+/// Finds all [DOIs](https://www.doi.org/) inside the `[package.metadata]` table of a `Cargo.toml`
+pub fn manifest_metadata_dois(manifest: &str) -> crate::Result<Vec<String>> {
+    fn collect_strings(value: &toml::Value, out: &mut Vec<String>) {
+        match value {
+            toml::Value::String(s) => out.push(s.clone()),
+            toml::Value::Array(a) => a.iter().for_each(|x| collect_strings(x, out)),
+            toml::Value::Table(t) => t.values().for_each(|x| collect_strings(x, out)),
+            _ => (),
+        }
+    }
+    let manifest: toml::Value = toml::from_str(manifest)?;
+    let mut strings = vec![];
+    if let Some(metadata) = manifest.get("package").and_then(|p| p.get("metadata")) {
+        collect_strings(metadata, &mut strings);
+    }
+    Ok(find_dois(&strings.join("\n")))
+}
+
+/// Obtains the rendered README of a crate from [crates.io](https://crates.io) while respecting
+/// the rate limit of the client
+pub async fn crates_io_readme(
+    client: &crate::CratesIoClient,
+    crate_name: &str,
+    version: &semver::Version,
+) -> crate::Result<String> {
+    let rq = format!("https://crates.io/api/v1/crates/{crate_name}/{version}/readme");
+    #[cfg(feature = "log")]
+    log::trace!("Requesting README from \"{rq}\"");
+    client
+        .throttle(async {
+            Ok(client
+                .http()
+                .get(rq)
+                .send()
+                .await?
+                .error_for_status()?
+                .text()
+                .await?)
+        })
+        .await
+}
+
+/// Searches the `README.md` and `Cargo.toml` of a repository at
+/// [github.com](https://github.com) for DOIs.
+///
+/// Returns the name of the file in which each DOI was found together with the DOI.
+pub async fn discover_dois(
+    client: &reqwest::Client,
+    repository: &str,
+    rev: Option<&str>,
+) -> crate::Result<Vec<(String, String)>> {
+    let Some((owner, repo)) = crate::github_owner_repo(repository) else {
+        return Ok(vec![]);
+    };
+    let rev = rev.unwrap_or("HEAD");
+    let mut found = vec![];
+    for filename in ["README.md", "Cargo.toml"] {
+        let rq = format!("https://raw.githubusercontent.com/{owner}/{repo}/{rev}/{filename}");
+        #[cfg(feature = "log")]
+        log::trace!("Searching \"{rq}\" for DOIs");
+        let response = client.get(&rq).send().await?;
+        if !response.status().is_success() {
+            continue;
+        }
+        let text = response.text().await?;
+        let dois = if filename == "Cargo.toml" {
+            manifest_metadata_dois(&text)?
+        } else {
+            find_dois(&text)
+        };
+        found.extend(dois.into_iter().map(|doi| (filename.to_string(), doi)));
+    }
+    Ok(found)
+}
+
+/// Resolves all discovered DOIs which are not yet part of the results via
+/// [crate::get_bibtex_doi].
+///
+/// The candidates are marked as [PlainBibLaTeX::discovered] and ranked below all other results.
+/// Failures are only logged since these candidates are optional.
+pub(crate) async fn resolve_discovered_dois(
+    client: &reqwest::Client,
+    repository: &str,
+    discovered: Vec<(String, String)>,
+    results: &[BibLaTeX],
+) -> Vec<BibLaTeX> {
+    let mut known: Vec<String> = results
+        .iter()
+        .filter_map(|r| match r {
+            BibLaTeX::Plain(PlainBibLaTeX { doi, .. }) => doi.clone(),
+            BibLaTeX::CratesIO(b) | BibLaTeX::Repository(b) | BibLaTeX::Zenodo(b) => b.doi.clone(),
+            BibLaTeX::CITATIONCFF(cff) => cff.doi.clone(),
+            BibLaTeX::SoftwareMetadata(m) => m.doi.clone(),
+        })
+        .map(|doi| crate::normalize_doi(&doi).to_lowercase())
+        .collect();
+    let mut candidates = vec![];
+    for (filename, doi) in discovered {
+        if known.contains(&doi.to_lowercase()) {
+            continue;
+        }
+        known.push(doi.to_lowercase());
+        #[cfg(feature = "log")]
+        log::trace!("Resolving DOI {doi} found in {filename}");
+        match crate::get_bibtex_doi(&doi, client.clone()).await {
+            Ok(Some(bibliography)) => candidates.push(BibLaTeX::Plain(PlainBibLaTeX {
+                bibliography,
+                repository: repository.to_string(),
+                filename,
+                doi: Some(doi),
+                selected: vec![],
+                discovered: true,
+            })),
+            #[allow(unused)]
+            Ok(None) => {
+                #[cfg(feature = "log")]
+                log::warn!("DOI {doi} found in {filename} does not exist");
+            }
+            #[allow(unused)]
+            Err(e) => {
+                #[cfg(feature = "log")]
+                log::warn!("Could not resolve DOI {doi} found in {filename}: \"{e}\"");
+            }
+        }
+    }
+    candidates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_dois_in_readme() {
+        let readme = r#"
+# my_crate
+[![DOI](https://zenodo.org/badge/DOI/10.5281/zenodo.8234.svg)](https://doi.org/10.5281/zenodo.8234)
+[![status](https://joss.theoj.org/papers/10.21105/joss.07723/status.svg)](https://joss.theoj.org/papers/10.21105/joss.07723)
+Archived at <https://zenodo.org/records/15549>.
+Please cite (doi:10.1016/S0092-8674(00)81683-9).
+Version 10.1 is not a DOI and neither is 210.12345/abc.
+"#;
+        assert_eq!(
+            find_dois(readme),
+            vec![
+                "10.5281/zenodo.8234",
+                "10.21105/joss.07723",
+                "10.5281/zenodo.15549",
+                "10.1016/S0092-8674(00)81683-9",
+            ]
+        );
+    }
+
+    #[test]
+    fn find_dois_in_manifest() -> crate::Result<()> {
+        let manifest = r#"
+[package]
+name = "my_crate"
+description = "Not cited: 10.1234/description"
+
+[package.metadata.citation]
+doi = "10.5281/zenodo.8234"
+papers = ["https://doi.org/10.21105/joss.07723"]
+"#;
+        assert_eq!(
+            manifest_metadata_dois(manifest)?,
+            vec!["10.5281/zenodo.8234", "10.21105/joss.07723"]
+        );
+        Ok(())
+    }
+}
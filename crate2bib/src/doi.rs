@@ -1,24 +1,477 @@
+use biblatex::{Chunk, Spanned};
+use serde::{Deserialize, Serialize};
+
+/// Base url of the [DOI](https://www.doi.org/) resolver
+pub const DOI_URL: &str = "https://doi.org";
+
+/// Errors which can occur while resolving a [DOI](https://www.doi.org/)
+#[derive(Clone, Debug, thiserror::Error)]
+pub enum DoiError {
+    /// The DOI is not registered
+    #[error("DOI {0} does not exist")]
+    NotFound(String),
+    /// The given string is not a valid DOI
+    #[error("{0} is not a valid DOI")]
+    Invalid(String),
+    /// The resolver answered with a format which can not be parsed such as an HTML landing page
+    #[error("DOI {doi} resolved to unsupported content type {content_type}")]
+    UnsupportedContentType {
+        /// The requested DOI
+        doi: String,
+        /// Content type of the response
+        content_type: String,
+    },
+}
+
+/// The agency at which a [DOI](https://www.doi.org/) was registered
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum RegistrationAgency {
+    /// [Crossref](https://www.crossref.org) which registers most journal articles
+    Crossref,
+    /// [DataCite](https://datacite.org) which registers datasets and software such as
+    /// [Zenodo](https://zenodo.org) records
+    DataCite,
+    /// [mEDRA](https://www.medra.org)
+    MEDRA,
+    /// Any other agency which may not support CSL-JSON
+    Other(String),
+}
+
+/// Strips prefixes such as `https://doi.org/` or `doi:` from a DOI
+pub fn normalize_doi(doi: &str) -> &str {
+    let doi = doi.trim();
+    [
+        "https://doi.org/",
+        "http://doi.org/",
+        "https://dx.doi.org/",
+        "http://dx.doi.org/",
+        "doi:",
+    ]
+    .iter()
+    .find_map(|prefix| doi.strip_prefix(prefix))
+    .unwrap_or(doi)
+}
+
+/// Determines the [RegistrationAgency] of a DOI via the `/ra` endpoint of the resolver
+pub async fn doi_registration_agency(
+    client: &reqwest::Client,
+    base_url: &str,
+    doi: &str,
+) -> crate::Result<RegistrationAgency> {
+    let doi = normalize_doi(doi);
+    #[cfg(feature = "log")]
+    log::trace!("Requesting registration agency of {doi}");
+    let response = client
+        .get(format!("{}/ra/{doi}", base_url.trim_end_matches('/')))
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<serde_json::Value>()
+        .await?;
+    let info = response.get(0);
+    let get_str = |key| info.and_then(|i| i.get(key)).and_then(|x| x.as_str());
+    match (get_str("RA"), get_str("status")) {
+        (Some("Crossref"), _) => Ok(RegistrationAgency::Crossref),
+        (Some("DataCite"), _) => Ok(RegistrationAgency::DataCite),
+        (Some("mEDRA"), _) => Ok(RegistrationAgency::MEDRA),
+        (Some(ra), _) => Ok(RegistrationAgency::Other(ra.to_string())),
+        (None, Some("Invalid DOI")) => Err(DoiError::Invalid(doi.to_string()).into()),
+        (None, _) => Err(DoiError::NotFound(doi.to_string()).into()),
+    }
+}
+
+/// Formats a CSL-JSON value which may either be a string, a number or a list of strings
+fn csl_text(value: Option<&serde_json::Value>) -> Option<String> {
+    match value? {
+        serde_json::Value::String(s) => Some(s.clone()),
+        serde_json::Value::Number(n) => Some(n.to_string()),
+        serde_json::Value::Array(a) => csl_text(a.first()),
+        _ => None,
+    }
+    .filter(|s| !s.trim().is_empty())
+}
+
+/// Extracts the date parts `[year, month, day]` of a CSL-JSON date.
+///
+/// DataCite sometimes gives the parts as strings instead of numbers.
+fn csl_date_parts(value: Option<&serde_json::Value>) -> Vec<i64> {
+    value
+        .and_then(|d| d.get("date-parts"))
+        .and_then(|d| d.get(0))
+        .and_then(|d| d.as_array())
+        .map(|parts| {
+            parts
+                .iter()
+                .map_while(|p| p.as_i64().or(p.as_str().and_then(|s| s.parse().ok())))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Converts a [CSL-JSON](https://citeproc-js.readthedocs.io/en/latest/csl-json/markup.html) item
+/// into a BibLaTeX entry.
+pub fn csl_json_to_biblatex(csl: &serde_json::Value) -> biblatex::Entry {
+    use biblatex::EntryType;
+    let get = |key| csl_text(csl.get(key));
+    let entry_type = match get("type").as_deref() {
+        Some("article-journal" | "journal-article") => EntryType::Article,
+        Some("book" | "monograph") => EntryType::Book,
+        Some("chapter" | "book-chapter") => EntryType::InBook,
+        Some("paper-conference" | "proceedings-article") => EntryType::InProceedings,
+        Some("dataset") => EntryType::Dataset,
+        Some("software") => EntryType::Software,
+        Some("report") => EntryType::Report,
+        Some("thesis" | "dissertation") => EntryType::Thesis,
+        _ => EntryType::Misc,
+    };
+
+    let authors: Vec<_> = csl
+        .get("author")
+        .and_then(|a| a.as_array())
+        .map(|authors| {
+            authors
+                .iter()
+                .map(|a| biblatex::Person {
+                    name: csl_text(a.get("family"))
+                        .or(csl_text(a.get("literal")))
+                        .or(csl_text(a.get("name")))
+                        .unwrap_or_default(),
+                    given_name: csl_text(a.get("given")).unwrap_or_default(),
+                    prefix: csl_text(a.get("non-dropping-particle")).unwrap_or_default(),
+                    suffix: csl_text(a.get("suffix")).unwrap_or_default(),
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let date = csl_date_parts(csl.get("issued").or(csl.get("published")));
+
+    let key = format!(
+        "{}{}",
+        authors
+            .first()
+            .map(|a| a.name.replace(' ', "_"))
+            .unwrap_or("doi".to_string()),
+        date.first().map(|y| format!("_{y}")).unwrap_or_default()
+    );
+    let mut entry = biblatex::Entry::new(key, entry_type);
+    let chunks = |s: String| vec![Spanned::detached(Chunk::Normal(s))];
+    if !authors.is_empty() {
+        entry.set_author(authors);
+    }
+    if !date.is_empty() {
+        let date = date
+            .iter()
+            .enumerate()
+            .map(|(n, x)| {
+                if n == 0 {
+                    format!("{x}")
+                } else {
+                    format!("{x:02}")
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("-");
+        entry.set("date", chunks(date));
+    }
+    for (csl_key, key) in [
+        ("title", "title"),
+        ("container-title", "journaltitle"),
+        ("volume", "volume"),
+        ("issue", "number"),
+        ("page", "pages"),
+        ("publisher", "publisher"),
+        ("ISSN", "issn"),
+        ("ISBN", "isbn"),
+        ("DOI", "doi"),
+        ("URL", "url"),
+        ("version", "version"),
+    ] {
+        if let Some(value) = get(csl_key) {
+            entry.set(key, chunks(value));
+        }
+    }
+    entry
+}
+
 /// Tries to obtain a bibtex entry from a given [DOI](https://www.doi.org/)
+///
+/// Uses [DOI_URL] to resolve the DOI (see [get_bibtex_doi_with_url]).
+/// Returns [None] if the DOI is not registered.
+/// All other failures such as invalid DOIs are returned as errors.
 pub async fn get_bibtex_doi(
     doi: &str,
     client: reqwest::Client,
 ) -> crate::Result<Option<biblatex::Bibliography>> {
-    // let doi = "10.1021/acs.jpcc.0c05161";
-    let rq = format!("https://doi.org/{doi}");
+    match get_bibtex_doi_with_url(doi, DOI_URL, &client).await {
+        Ok(bibliography) => Ok(Some(bibliography)),
+        Err(crate::Err::Doi(DoiError::NotFound(_))) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+const CSL_JSON: &str = "application/vnd.citationstyles.csl+json";
+const BIBTEX: &str = "application/x-bibtex";
+
+/// Tries to obtain a bibtex entry from a given [DOI](https://www.doi.org/) via the resolver at
+/// `base_url`.
+///
+/// For DOIs registered at Crossref, DataCite or mEDRA, structured CSL-JSON is requested and
+/// converted via [csl_json_to_biblatex].
+/// Other agencies are asked for `application/x-bibtex` instead.
+/// If the agency can not be determined or the CSL-JSON request fails, BibTeX is requested as well.
+pub async fn get_bibtex_doi_with_url(
+    doi: &str,
+    base_url: &str,
+    client: &reqwest::Client,
+) -> crate::Result<biblatex::Bibliography> {
+    let doi = normalize_doi(doi);
+    let agency = match doi_registration_agency(client, base_url, doi).await {
+        Ok(agency) => Some(agency),
+        Err(e @ crate::Err::Doi(_)) => return Err(e),
+        #[allow(unused)]
+        Err(e) => {
+            #[cfg(feature = "log")]
+            log::warn!("Could not determine the registration agency of {doi}: \"{e}\"");
+            None
+        }
+    };
+    let rq = format!("{}/{doi}", base_url.trim_end_matches('/'));
 
     #[cfg(feature = "log")]
-    log::trace!("Sending request to doi.org");
+    log::trace!("Sending request to doi.org for {doi} registered at {agency:?}");
+    if matches!(
+        agency,
+        Some(
+            RegistrationAgency::Crossref | RegistrationAgency::DataCite | RegistrationAgency::MEDRA
+        )
+    ) {
+        match request_doi(client, &rq, doi, CSL_JSON).await {
+            Err(crate::Err::Doi(DoiError::NotFound(d))) => return Err(DoiError::NotFound(d).into()),
+            #[allow(unused)]
+            Err(e) => {
+                #[cfg(feature = "log")]
+                log::warn!("Could not obtain CSL-JSON for {doi}: \"{e}\". Requesting BibTeX.");
+            }
+            Ok(bibliography) => return Ok(bibliography),
+        }
+    }
+    request_doi(client, &rq, doi, BIBTEX).await
+}
+
+/// Requests the DOI with the given `accept` header and parses the response
+async fn request_doi(
+    client: &reqwest::Client,
+    rq: &str,
+    doi: &str,
+    accept: &str,
+) -> crate::Result<biblatex::Bibliography> {
     let res = client
         .request(reqwest::Method::GET, rq)
-        .header(reqwest::header::ACCEPT, "application/x-bibtex")
+        .header(reqwest::header::ACCEPT, accept)
         .send()
         .await?;
+    if res.status() == reqwest::StatusCode::NOT_FOUND {
+        return Err(DoiError::NotFound(doi.to_string()).into());
+    }
+    let res = res.error_for_status()?;
+    let content_type = res
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|c| c.to_str().ok())
+        .unwrap_or_default()
+        .to_string();
 
     #[cfg(feature = "log")]
-    log::trace!("Parsing request to biblatex");
-    if let Ok(bib) = res.text().await {
-        Ok(Some(biblatex::Bibliography::parse(&bib)?))
+    log::trace!("Parsing response of type {content_type} to biblatex");
+    if content_type.contains("json") {
+        let csl = res.json::<serde_json::Value>().await?;
+        let mut bibliography = biblatex::Bibliography::new();
+        bibliography.insert(csl_json_to_biblatex(&csl));
+        Ok(bibliography)
+    } else if content_type.contains("bibtex") || content_type.starts_with("text/plain") {
+        crate::parse_bibliography(&res.text().await?)
     } else {
-        Ok(None)
+        Err(DoiError::UnsupportedContentType {
+            doi: doi.to_string(),
+            content_type,
+        }
+        .into())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn mock_ra(server: &mut mockito::ServerGuard, doi: &str, body: &str) {
+        server
+            .mock("GET", format!("/ra/{doi}").as_str())
+            .with_body(body)
+            .create_async()
+            .await;
+    }
+
+    #[tokio::test]
+    async fn resolve_crossref_csl_json() -> crate::Result<()> {
+        let mut server = mockito::Server::new_async().await;
+        let doi = "10.21105/joss.07723";
+        mock_ra(
+            &mut server,
+            doi,
+            r#"[{"DOI": "10.21105/joss.07723", "RA": "Crossref"}]"#,
+        )
+        .await;
+        server
+            .mock("GET", format!("/{doi}").as_str())
+            .match_header("accept", "application/vnd.citationstyles.csl+json")
+            .with_header("content-type", "application/vnd.citationstyles.csl+json")
+            .with_body(
+                r#"{
+                    "type": "article-journal",
+                    "publisher": "The Open Journal",
+                    "issue": "110",
+                    "DOI": "10.21105/joss.07723",
+                    "page": "7723",
+                    "title": "cellular_raza: Cellular Agent-based Modeling from a Clean Slate",
+                    "volume": "10",
+                    "author": [
+                        {"given": "Jonas", "family": "Pleyer", "sequence": "first"},
+                        {"given": "Christian", "family": "Fleck", "sequence": "additional"}
+                    ],
+                    "container-title": "Journal of Open Source Software",
+                    "ISSN": ["2475-9066"],
+                    "URL": "http://dx.doi.org/10.21105/joss.07723",
+                    "issued": {"date-parts": [[2025, 6, 4]]}
+                }"#,
+            )
+            .create_async()
+            .await;
+        let bib = get_bibtex_doi_with_url(
+            &format!("https://doi.org/{doi}"),
+            &server.url(),
+            &reqwest::Client::new(),
+        )
+        .await?;
+        let expected = r#"@article{Pleyer_2025,
+author = {Pleyer, Jonas and Fleck, Christian},
+date = {2025-06-04},
+doi = {10.21105/joss.07723},
+issn = {2475-9066},
+journaltitle = {Journal of Open Source Software},
+number = {110},
+pages = {7723},
+publisher = {The Open Journal},
+title = {cellular\_raza: Cellular Agent-based Modeling from a Clean Slate},
+url = {http://dx.doi.org/10.21105/joss.07723},
+volume = {10},
+}
+"#;
+        assert_eq!(bib.to_biblatex_string(), expected);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn resolve_datacite_and_other_agencies() -> crate::Result<()> {
+        let mut server = mockito::Server::new_async().await;
+        mock_ra(&mut server, "10.5281/zenodo.1", r#"[{"RA": "DataCite"}]"#).await;
+        server
+            .mock("GET", "/10.5281/zenodo.1")
+            .with_header("content-type", "application/vnd.citationstyles.csl+json")
+            .with_body(
+                r#"{
+                    "type": "software",
+                    "author": [{"literal": "The cellular_raza developers"}],
+                    "title": "cellular_raza",
+                    "issued": {"date-parts": [["2024"]]},
+                    "version": "0.2.4"
+                }"#,
+            )
+            .create_async()
+            .await;
+        let bib =
+            get_bibtex_doi_with_url("10.5281/zenodo.1", &server.url(), &reqwest::Client::new())
+                .await?;
+        let entry = bib.iter().next().unwrap();
+        assert_eq!(entry.entry_type, biblatex::EntryType::Software);
+        assert_eq!(entry.key, "The_cellular_raza_developers_2024");
+
+        mock_ra(&mut server, "10.1234/kisti", r#"[{"RA": "KISTI"}]"#).await;
+        server
+            .mock("GET", "/10.1234/kisti")
+            .match_header("accept", "application/x-bibtex")
+            .with_header("content-type", "text/html")
+            .with_body("<html></html>")
+            .create_async()
+            .await;
+        let res =
+            get_bibtex_doi_with_url("10.1234/kisti", &server.url(), &reqwest::Client::new()).await;
+        assert!(matches!(
+            res,
+            Err(crate::Err::Doi(DoiError::UnsupportedContentType { .. }))
+        ));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn fall_back_to_bibtex() -> crate::Result<()> {
+        let mut server = mockito::Server::new_async().await;
+        let doi = "10.21105/joss.07723";
+        mock_ra(&mut server, doi, r#"[{"RA": "Crossref"}]"#).await;
+        server
+            .mock("GET", format!("/{doi}").as_str())
+            .match_header("accept", CSL_JSON)
+            .with_status(503)
+            .create_async()
+            .await;
+        server
+            .mock("GET", format!("/{doi}").as_str())
+            .match_header("accept", BIBTEX)
+            .with_header("content-type", "application/x-bibtex")
+            .with_body("@article{Pleyer_2025, title = {cellular\\_raza}, year = {2025}}")
+            .create_async()
+            .await;
+        let bib = get_bibtex_doi_with_url(doi, &server.url(), &reqwest::Client::new()).await?;
+        assert_eq!(bib.iter().next().unwrap().key, "Pleyer_2025");
+
+        // The registration agency could not be determined
+        server
+            .mock("GET", "/ra/10.1234/unknown-ra")
+            .with_status(500)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/10.1234/unknown-ra")
+            .match_header("accept", BIBTEX)
+            .with_header("content-type", "text/plain")
+            .with_body("@misc{Doe_2020, title = {Data}}")
+            .create_async()
+            .await;
+        let bib =
+            get_bibtex_doi_with_url("10.1234/unknown-ra", &server.url(), &reqwest::Client::new())
+                .await?;
+        assert_eq!(bib.iter().next().unwrap().key, "Doe_2020");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn unknown_doi() {
+        let mut server = mockito::Server::new_async().await;
+        mock_ra(
+            &mut server,
+            "10.1234/unknown",
+            r#"[{"DOI": "10.1234/unknown", "status": "DOI does not exist"}]"#,
+        )
+        .await;
+        mock_ra(
+            &mut server,
+            "invalid",
+            r#"[{"DOI": "invalid", "status": "Invalid DOI"}]"#,
+        )
+        .await;
+        let client = reqwest::Client::new();
+        let res = get_bibtex_doi_with_url("10.1234/unknown", &server.url(), &client).await;
+        assert!(matches!(res, Err(crate::Err::Doi(DoiError::NotFound(_)))));
+        let res = get_bibtex_doi_with_url("invalid", &server.url(), &client).await;
+        assert!(matches!(res, Err(crate::Err::Doi(DoiError::Invalid(_)))));
     }
 }
@@ -0,0 +1,518 @@
+use biblatex::{Chunk, ChunksExt, Spanned};
+use chrono::Datelike;
+
+use crate::{BibLaTeXCratesIO, Encoding};
+
+/// Order in which well-known fields are written by [entry_to_biblatex_string].
+///
+/// All other fields follow in alphabetical order.
+const FIELD_ORDER: &[&str] = &[
+    "author",
+    "editor",
+    "title",
+    "subtitle",
+    "journaltitle",
+    "booktitle",
+    "volume",
+    "number",
+    "pages",
+    "publisher",
+    "institution",
+    "organization",
+    "url",
+    "doi",
+    "date",
+    "year",
+    "month",
+    "version",
+    "license",
+    "keywords",
+    "eprinttype",
+    "eprint",
+    "note",
+];
+
+/// Fields whose values are written without escaping such as urls
+fn is_verbatim_field(field: &str) -> bool {
+    matches!(
+        field,
+        "url" | "urlraw" | "doi" | "eprint" | "file" | "uri" | "pdf"
+    )
+}
+
+/// Splits text into chunks where groups in balanced braces such as `{cellular_raza}` are kept
+/// verbatim
+fn text_to_chunks(text: &str) -> biblatex::Chunks {
+    let mut chunks = vec![];
+    let mut current = String::new();
+    let mut depth = 0;
+    let chars: Vec<_> = text.chars().collect();
+    for (n, c) in chars.iter().enumerate() {
+        match c {
+            '{' if depth == 0 && chars[n..].contains(&'}') => {
+                if !current.is_empty() {
+                    chunks.push(Chunk::Normal(std::mem::take(&mut current)));
+                }
+                depth += 1;
+            }
+            '}' if depth == 1 => {
+                chunks.push(Chunk::Verbatim(std::mem::take(&mut current)));
+                depth = 0;
+            }
+            c => {
+                match c {
+                    '{' => depth += usize::from(depth > 0),
+                    '}' => depth -= usize::from(depth > 1),
+                    _ => (),
+                }
+                current.push(*c);
+            }
+        }
+    }
+    if !current.is_empty() {
+        chunks.push(if depth > 0 {
+            Chunk::Verbatim(current)
+        } else {
+            Chunk::Normal(current)
+        });
+    }
+    chunks.into_iter().map(Spanned::detached).collect()
+}
+
+/// Inverse of [text_to_chunks] which writes verbatim chunks in braces
+fn chunks_to_text(chunks: biblatex::ChunksRef) -> String {
+    chunks
+        .iter()
+        .map(|chunk| match &chunk.v {
+            Chunk::Normal(s) => s.clone(),
+            Chunk::Verbatim(s) => format!("{{{s}}}"),
+            Chunk::Math(s) => format!("${s}$"),
+        })
+        .collect()
+}
+
+fn normal(text: &str) -> biblatex::Chunks {
+    vec![Spanned::detached(Chunk::Normal(text.to_string()))]
+}
+
+/// Splits a name such as `Jane Doe` or `Doe, Jane` into its parts
+pub fn person_from_name(name: &str) -> biblatex::Person {
+    biblatex::Person::parse(&normal(name.trim()))
+}
+
+/// Creates an author which is an organization such as `ACME, Inc.` and written in braces
+pub fn organization(name: &str) -> biblatex::Person {
+    biblatex::Person {
+        name: name.trim().to_string(),
+        given_name: String::new(),
+        prefix: String::new(),
+        suffix: String::new(),
+    }
+}
+
+/// Removes characters such as spaces, commas or braces which are not allowed in citation keys
+pub fn sanitize_key(key: &str) -> String {
+    key.chars()
+        .filter(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | ':' | '.' | '/' | '+'))
+        .collect()
+}
+
+/// Writes the authors separated by `and` while names which contain spaces or commas such as
+/// organizations are protected by braces
+fn persons_to_chunks(persons: &[biblatex::Person]) -> biblatex::Chunks {
+    let mut chunks = vec![];
+    let name = |name: &str| {
+        if name.contains([' ', ',']) {
+            Chunk::Verbatim(name.to_string())
+        } else {
+            Chunk::Normal(name.to_string())
+        }
+    };
+    for (n, p) in persons.iter().enumerate() {
+        if n > 0 {
+            chunks.push(Chunk::Normal(" and ".to_string()));
+        }
+        let prefix = [p.given_name.as_str(), p.prefix.as_str()]
+            .into_iter()
+            .filter(|x| !x.is_empty())
+            .map(|x| format!("{x} "))
+            .collect::<String>();
+        if p.suffix.is_empty() {
+            if !prefix.is_empty() {
+                chunks.push(Chunk::Normal(prefix));
+            }
+            chunks.push(name(&p.name));
+        } else {
+            if !p.prefix.is_empty() {
+                chunks.push(Chunk::Normal(format!("{} ", p.prefix)));
+            }
+            chunks.push(name(&p.name));
+            chunks.push(Chunk::Normal(format!(", {}, {}", p.suffix, p.given_name)));
+        }
+    }
+    chunks.into_iter().map(Spanned::detached).collect()
+}
+
+/// Serializes authors as a list of their name parts
+pub(crate) mod serde_persons {
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    #[derive(Deserialize, Serialize)]
+    struct Name {
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        given: String,
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        prefix: String,
+        family: String,
+        #[serde(default, skip_serializing_if = "String::is_empty")]
+        suffix: String,
+    }
+
+    pub fn serialize<S: Serializer>(
+        persons: &[biblatex::Person],
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        persons
+            .iter()
+            .map(|p| Name {
+                given: p.given_name.clone(),
+                prefix: p.prefix.clone(),
+                family: p.name.clone(),
+                suffix: p.suffix.clone(),
+            })
+            .collect::<Vec<_>>()
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<biblatex::Person>, D::Error> {
+        Ok(Vec::<Name>::deserialize(deserializer)?
+            .into_iter()
+            .map(|n| biblatex::Person {
+                name: n.family,
+                given_name: n.given,
+                prefix: n.prefix,
+                suffix: n.suffix,
+            })
+            .collect())
+    }
+}
+
+impl BibLaTeXCratesIO {
+    /// The names of all authors separated by commas such as `Jonas Pleyer, Christian Fleck`
+    pub fn author_names(&self) -> String {
+        self.author
+            .iter()
+            .map(|p| p.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    /// Converts the entry into a [biblatex::Entry].
+    ///
+    /// Authors are joined by `and` as required by BibLaTeX and the key is stripped of characters
+    /// which are not allowed (see [sanitize_key]).
+    /// The fields [BibLaTeXCratesIO::yanked] and [BibLaTeXCratesIO::notices] have no BibLaTeX
+    /// equivalent and are dropped.
+    pub fn to_entry(&self) -> biblatex::Entry {
+        let mut entry = biblatex::Entry::new(
+            sanitize_key(&self.key),
+            biblatex::EntryType::new(&self.work_type),
+        );
+        if !self.author.is_empty() {
+            entry.set("author", persons_to_chunks(&self.author));
+        }
+        entry.set("title", text_to_chunks(&self.title));
+        if let Some(url) = &self.url {
+            entry.set("url", normal(url));
+        }
+        if let Some(doi) = &self.doi {
+            entry.set("doi", normal(doi));
+        }
+        if let Some(date) = self.date {
+            entry.set(
+                "date",
+                normal(&format!(
+                    "{:4.0}-{:02}-{:02}",
+                    date.year(),
+                    date.month(),
+                    date.day()
+                )),
+            );
+        }
+        if let Some(version) = &self.version {
+            entry.set("version", normal(&version.to_string()));
+        }
+        if let Some(license) = &self.license {
+            entry.set("license", text_to_chunks(license));
+        }
+        if !self.keywords.is_empty() {
+            entry.set("keywords", normal(&self.keywords.join(", ")));
+        }
+        if let Some(swhid) = &self.swhid {
+            entry.set("eprinttype", normal("swh"));
+            entry.set("eprint", normal(swhid));
+        }
+        if let Some(note) = &self.note {
+            entry.set("note", text_to_chunks(note));
+        }
+        entry
+    }
+
+    /// Reads all known fields from a [biblatex::Entry].
+    ///
+    /// Braced groups in titles are kept.
+    pub fn from_entry(entry: &biblatex::Entry) -> Self {
+        let date = match entry.date() {
+            Ok(biblatex::PermissiveType::Typed(biblatex::Date {
+                value: biblatex::DateValue::At(d),
+                ..
+            })) => chrono::NaiveDate::from_ymd_opt(
+                d.year,
+                d.month.map_or(1, |m| m as u32 + 1),
+                d.day.map_or(1, |d| d as u32 + 1),
+            )
+            .and_then(|d| d.and_hms_opt(0, 0, 0))
+            .map(|d| d.and_utc()),
+            _ => None,
+        };
+        let text = |field| entry.get(field).map(chunks_to_text);
+        BibLaTeXCratesIO {
+            key: entry.key.clone(),
+            work_type: entry.entry_type.to_string(),
+            author: entry.author().unwrap_or_default(),
+            title: text("title").unwrap_or_default(),
+            url: entry.url().ok(),
+            doi: entry.doi().ok(),
+            license: text("license"),
+            version: entry
+                .version()
+                .ok()
+                .and_then(|v| semver::Version::parse(&v.format_verbatim()).ok()),
+            date,
+            keywords: entry
+                .get_as::<Vec<String>>("keywords")
+                .unwrap_or_default()
+                .into_iter()
+                .map(|k| k.trim().to_string())
+                .filter(|k| !k.is_empty())
+                .collect(),
+            swhid: entry
+                .get("eprinttype")
+                .is_some_and(|t| t.format_verbatim() == "swh")
+                .then(|| entry.get("eprint").map(|e| e.format_verbatim()))
+                .flatten(),
+            note: text("note"),
+            ..Default::default()
+        }
+    }
+}
+
+impl From<&BibLaTeXCratesIO> for biblatex::Entry {
+    fn from(value: &BibLaTeXCratesIO) -> Self {
+        value.to_entry()
+    }
+}
+
+impl From<&biblatex::Entry> for BibLaTeXCratesIO {
+    fn from(value: &biblatex::Entry) -> Self {
+        BibLaTeXCratesIO::from_entry(value)
+    }
+}
+
+/// Parses a bibliography while keeping LaTeX commands such as `\textit{fast}` or `\&` as they
+/// were written.
+///
+/// [biblatex] decodes some commands but not others which is why its chunks can not be escaped
+/// again without ambiguity.
+/// The chunks returned by this function contain the source text instead and are written
+/// unchanged by [entry_to_biblatex_string].
+pub fn parse_bibliography(text: &str) -> crate::Result<biblatex::Bibliography> {
+    let mut bibliography = biblatex::Bibliography::parse(text)?;
+    for entry in bibliography.iter_mut() {
+        for chunks in entry.fields.values_mut() {
+            for chunk in chunks.iter_mut() {
+                let Some(raw) = text.get(chunk.span.clone()) else {
+                    continue;
+                };
+                // Expanded abbreviations such as `jun` contain no commands and stay decoded
+                if !raw.contains('\\') {
+                    continue;
+                }
+                match &mut chunk.v {
+                    Chunk::Normal(s) | Chunk::Verbatim(s) | Chunk::Math(s) => *s = raw.to_string(),
+                }
+            }
+        }
+    }
+    Ok(bibliography)
+}
+
+/// Chunks which were read from a file keep their span while generated chunks are detached
+fn is_parsed(chunk: &Spanned<Chunk>) -> bool {
+    chunk.span != Spanned::detached(()).span
+}
+
+pub(crate) fn write_value(field: &str, chunks: biblatex::ChunksRef, encoding: Encoding) -> String {
+    if is_verbatim_field(field) {
+        return chunks.format_verbatim();
+    }
+    chunks
+        .iter()
+        .map(|chunk| match &chunk.v {
+            Chunk::Normal(s) if is_parsed(chunk) => crate::encode_non_ascii(s, encoding),
+            Chunk::Verbatim(s) if is_parsed(chunk) => {
+                format!("{{{}}}", crate::encode_non_ascii(s, encoding))
+            }
+            Chunk::Normal(s) if field == "title" => crate::escape_latex_title(s, encoding),
+            Chunk::Normal(s) => crate::escape_latex(s, encoding),
+            Chunk::Verbatim(s) => format!("{{{}}}", crate::escape_latex(s, encoding)),
+            Chunk::Math(s) => format!("${s}$"),
+        })
+        .collect()
+}
+
+/// Serializes an entry into a BibLaTeX string.
+///
+/// Well-known fields are written in a fixed order.
+/// Values which crate2bib generated from plain text are escaped with the given [Encoding] (see
+/// [crate::escape_latex]) except for verbatim fields such as `url` or `doi`.
+/// Values read by [parse_bibliography] are already LaTeX and are written unchanged apart from
+/// the encoding of non-ASCII characters.
+pub fn entry_to_biblatex_string(entry: &biblatex::Entry, encoding: Encoding) -> String {
+    let mut fields: Vec<_> = entry.fields.iter().collect();
+    fields.sort_by_key(|(field, _)| {
+        (
+            FIELD_ORDER
+                .iter()
+                .position(|f| f == field)
+                .unwrap_or(FIELD_ORDER.len()),
+            field.to_string(),
+        )
+    });
+    let mut out = format!("@{}{{{},\n", entry.entry_type.to_biblatex(), entry.key);
+    for (field, chunks) in fields {
+        out.push_str(&format!(
+            "    {field} = {{{}}},\n",
+            write_value(field, chunks, encoding)
+        ));
+    }
+    out.push('}');
+    out
+}
+
+/// Serializes multiple entries with [entry_to_biblatex_string] separated by empty lines
+pub fn entries_to_biblatex_string<'a>(
+    entries: impl IntoIterator<Item = &'a biblatex::Entry>,
+    encoding: Encoding,
+) -> String {
+    entries
+        .into_iter()
+        .map(|e| entry_to_biblatex_string(e, encoding))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip_entry() {
+        let original = BibLaTeXCratesIO {
+            key: "Pleyer2025".to_string(),
+            work_type: "software".to_string(),
+            author: vec![
+                crate::person_from_name("Jonas Pleyer"),
+                crate::person_from_name("Christian Fleck"),
+            ],
+            title: "{cellular_raza}: Cellular Agent-based Modeling from a Clean Slate".to_string(),
+            url: Some("https://github.com/jonaspleyer/cellular_raza".to_string()),
+            doi: Some("10.21105/joss.07723".to_string()),
+            license: Some("GPL-2.0".to_string()),
+            version: Some(semver::Version::new(0, 2, 4)),
+            date: chrono::NaiveDate::from_ymd_opt(2025, 6, 1)
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+                .map(|d| d.and_utc()),
+            keywords: vec!["agent-based".to_string(), "simulation".to_string()],
+            swhid: Some("swh:1:rev:0123".to_string()),
+            note: Some("Cite the paper & the software".to_string()),
+            ..Default::default()
+        };
+        let entry = original.to_entry();
+        let expected = "\
+@software{Pleyer2025,
+    author = {Jonas Pleyer and Christian Fleck},
+    title = {{cellular\\_raza}: Cellular Agent-based Modeling from a Clean Slate},
+    url = {https://github.com/jonaspleyer/cellular_raza},
+    doi = {10.21105/joss.07723},
+    date = {2025-06-01},
+    version = {0.2.4},
+    license = {GPL-2.0},
+    keywords = {agent-based, simulation},
+    eprinttype = {swh},
+    eprint = {swh:1:rev:0123},
+    note = {Cite the paper \\& the software},
+}";
+        let output = entry_to_biblatex_string(&entry, Encoding::Utf8);
+        assert_eq!(output, expected);
+
+        // Parsing the output yields the same entry
+        let parsed = biblatex::Bibliography::parse(&output).unwrap();
+        let back = BibLaTeXCratesIO::from_entry(parsed.get("Pleyer2025").unwrap());
+        assert_eq!(back.to_entry(), entry);
+        assert_eq!(back.author, original.author);
+        assert_eq!(back.title, original.title);
+        assert_eq!(back.keywords, original.keywords);
+        assert_eq!(back.swhid, original.swhid);
+        assert_eq!(back.date, original.date);
+    }
+
+    #[test]
+    fn organizations_as_authors() -> crate::Result<()> {
+        let cff = citeworks_cff::from_str(
+            "cff-version: 1.2.0
+message: Please cite
+title: rocket
+authors:
+  - name: ACME, Inc.
+  - given-names: Jane
+    family-names: Doe
+date-released: 2024-01-02
+",
+        )?;
+        let entry = BibLaTeXCratesIO::from_citation_cff(&cff)
+            .unwrap()
+            .to_entry();
+        let output = entry_to_biblatex_string(&entry, Encoding::Utf8);
+        assert!(output
+            .starts_with("@software{ACMEInc.2024,\n    author = {{ACME, Inc.} and Jane Doe},"));
+        let parsed = biblatex::Bibliography::parse(&output)?;
+        let authors = parsed.get("ACMEInc.2024").unwrap().author().unwrap();
+        assert_eq!(authors.len(), 2);
+        assert_eq!(authors[0].name, "ACME, Inc.");
+        assert_eq!(authors[1].given_name, "Jane");
+        Ok(())
+    }
+
+    #[test]
+    fn keep_latex_of_parsed_entries() -> crate::Result<()> {
+        let input = "\
+@article{Doe2020,
+    author = {Doe, Jane and {ACME, Inc.}},
+    title = {A \\textit{fast} {GPU} solver for $x^2$ in \\LaTeX{} with 5\\,\\% \\& a\\_b},
+    journaltitle = {J. of {Stuff}},
+    doi = {10.1234/a_b},
+    note = {See \\url{https://example.org/a_b} and caf\\'e},
+}";
+        let bibliography = parse_bibliography(input)?;
+        let output = entries_to_biblatex_string(bibliography.iter(), Encoding::Utf8);
+        assert_eq!(output, input);
+        assert_eq!(
+            entries_to_biblatex_string(bibliography.iter(), Encoding::Latex),
+            input
+        );
+        Ok(())
+    }
+}
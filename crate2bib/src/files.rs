@@ -0,0 +1,292 @@
+use std::path::Path;
+
+/// Conventional names of citation files which are searched by default.
+///
+/// Patterns are matched case-insensitively against the path relative to the root of the
+/// repository (see [glob_match]).
+pub const CITATION_FILE_PATTERNS: &[&str] = &[
+    "CITATION.cff",
+    "CITATION.bib",
+    "CITATION.md",
+    "CITATION",
+    "docs/citation.bib",
+    "paper/paper.bib",
+    "paper.bib",
+    ".zenodo.json",
+    "codemeta.json",
+];
+
+fn glob_match_segments(pattern: &[&str], path: &[&str]) -> bool {
+    match (pattern.first(), path.first()) {
+        (None, None) => true,
+        (Some(&"**"), _) => {
+            glob_match_segments(&pattern[1..], path)
+                || (!path.is_empty() && glob_match_segments(pattern, &path[1..]))
+        }
+        (Some(p), Some(s)) => {
+            glob_match_segment(
+                &p.chars().collect::<Vec<_>>(),
+                &s.chars().collect::<Vec<_>>(),
+            ) && glob_match_segments(&pattern[1..], &path[1..])
+        }
+        _ => false,
+    }
+}
+
+fn glob_match_segment(pattern: &[char], name: &[char]) -> bool {
+    match (pattern.first(), name.first()) {
+        (None, None) => true,
+        (Some('*'), _) => {
+            glob_match_segment(&pattern[1..], name)
+                || (!name.is_empty() && glob_match_segment(pattern, &name[1..]))
+        }
+        (Some('?'), Some(_)) => glob_match_segment(&pattern[1..], &name[1..]),
+        (Some(p), Some(n)) => p == n && glob_match_segment(&pattern[1..], &name[1..]),
+        _ => false,
+    }
+}
+
+/// Matches a path against a glob pattern ignoring case.
+///
+/// - `?` matches any single character
+/// - `*` matches any number of characters within a single path segment
+/// - `**` matches any number of path segments such as in `**/citation.bib`
+pub fn glob_match(pattern: &str, path: &str) -> bool {
+    let pattern = pattern.to_lowercase();
+    let path = path.to_lowercase();
+    let pattern: Vec<_> = pattern.split('/').filter(|s| !s.is_empty()).collect();
+    let path: Vec<_> = path.split('/').filter(|s| !s.is_empty()).collect();
+    glob_match_segments(&pattern, &path)
+}
+
+/// Format of a citation file which determines how it is parsed
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CitationFormat {
+    /// A BibTeX or BibLaTeX bibliography
+    BibLaTeX,
+    /// A [`CITATION.cff`](https://citation-file-format.github.io) file
+    CitationCff,
+    /// A `.zenodo.json` file (see [crate::SoftwareMetadata::from_zenodo_json])
+    ZenodoJson,
+    /// A `codemeta.json` file (see [crate::SoftwareMetadata::from_codemeta])
+    CodeMeta,
+}
+
+impl std::str::FromStr for CitationFormat {
+    type Err = crate::Err;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "bib" | "bibtex" | "biblatex" => Ok(CitationFormat::BibLaTeX),
+            "cff" => Ok(CitationFormat::CitationCff),
+            "zenodo" | "zenodo.json" => Ok(CitationFormat::ZenodoJson),
+            "codemeta" | "codemeta.json" => Ok(CitationFormat::CodeMeta),
+            x => Err(crate::Err::FiletypeUnsupported(format!(
+                "the {x} format is currently not supported"
+            ))),
+        }
+    }
+}
+
+impl CitationFormat {
+    /// Determines the format from the final extension of the filename ignoring case
+    pub fn from_filename(filename: &str) -> Option<Self> {
+        let name = filename.rsplit('/').next()?.to_lowercase();
+        if name.ends_with(".zenodo.json") {
+            return Some(CitationFormat::ZenodoJson);
+        }
+        if name == "codemeta.json" {
+            return Some(CitationFormat::CodeMeta);
+        }
+        match name.rsplit_once('.')?.1 {
+            "bib" | "bibtex" => Some(CitationFormat::BibLaTeX),
+            "cff" => Some(CitationFormat::CitationCff),
+            _ => None,
+        }
+    }
+
+    /// Guesses the format from the contents of a file
+    pub fn sniff(text: &str) -> Option<Self> {
+        let trimmed = text.trim_start();
+        if trimmed.starts_with('{') {
+            return if trimmed.contains("\"@context\"") {
+                Some(CitationFormat::CodeMeta)
+            } else if trimmed.contains("\"creators\"") {
+                Some(CitationFormat::ZenodoJson)
+            } else {
+                None
+            };
+        }
+        let lines = || text.lines().map(|l| l.trim_start());
+        if lines().any(|l| l.starts_with("cff-version:")) {
+            return Some(CitationFormat::CitationCff);
+        }
+        let is_entry_header = |line: &str| {
+            line.strip_prefix('@').is_some_and(|rest| {
+                let name: String = rest
+                    .chars()
+                    .take_while(|c| c.is_ascii_alphabetic())
+                    .collect();
+                !name.is_empty() && rest[name.len()..].trim_start().starts_with(['{', '('])
+            })
+        };
+        lines()
+            .any(is_entry_header)
+            .then_some(CitationFormat::BibLaTeX)
+    }
+
+    /// Determines the format by its filename and falls back to [CitationFormat::sniff]
+    pub fn detect(filename: &str, text: &str) -> Option<Self> {
+        Self::from_filename(filename).or_else(|| Self::sniff(text))
+    }
+}
+
+/// Splits an explicit format override such as in `refs.txt:bib` from a file pattern
+pub fn parse_file_pattern(pattern: &str) -> (&str, Option<CitationFormat>) {
+    match pattern
+        .rsplit_once(':')
+        .and_then(|(p, format)| Some((p, format.parse().ok()?)))
+    {
+        Some((p, format)) => (p, Some(format)),
+        None => (pattern, None),
+    }
+}
+
+/// Selects all paths which match any of the given patterns.
+///
+/// Patterns may specify the format of matching files explicitly (see [parse_file_pattern]).
+/// The paths are returned in the order of the patterns which they match first.
+pub fn match_citation_files<'a>(
+    paths: impl IntoIterator<Item = &'a str>,
+    patterns: &[&str],
+) -> Vec<(String, Option<CitationFormat>)> {
+    let paths: Vec<_> = paths.into_iter().collect();
+    let mut found: Vec<(String, Option<CitationFormat>)> = vec![];
+    for pattern in patterns {
+        let (pattern, format) = parse_file_pattern(pattern);
+        for path in paths.iter() {
+            if glob_match(pattern, path) && !found.iter().any(|(f, _)| f == path) {
+                found.push((path.to_string(), format));
+            }
+        }
+    }
+    found
+}
+
+/// Lists all files inside a local directory relative to it.
+///
+/// Hidden directories and `target` folders are skipped.
+pub(crate) fn list_local_files(root: &Path) -> Vec<String> {
+    fn visit(root: &Path, dir: &Path, depth: usize, out: &mut Vec<String>) {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name().to_string_lossy().to_string();
+            if path.is_dir() {
+                if depth > 0 && !name.starts_with('.') && name != "target" {
+                    visit(root, &path, depth - 1, out);
+                }
+            } else if let Ok(relative) = path.strip_prefix(root) {
+                out.push(relative.to_string_lossy().replace('\\', "/"));
+            }
+        }
+    }
+    let mut files = vec![];
+    visit(root, root, 3, &mut files);
+    files.sort();
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn match_conventional_names() {
+        let tree = [
+            "Cargo.toml",
+            "citation.CFF",
+            "docs/Citation.bib",
+            "paper/paper.bib",
+            "paper/paper.md",
+            "src/lib.rs",
+            ".zenodo.json",
+            "crates/sub/CITATION.cff",
+        ];
+        let names = |found: Vec<(String, Option<CitationFormat>)>| -> Vec<String> {
+            found.into_iter().map(|(f, _)| f).collect()
+        };
+        assert_eq!(
+            names(match_citation_files(tree, CITATION_FILE_PATTERNS)),
+            vec![
+                "citation.CFF",
+                "docs/Citation.bib",
+                "paper/paper.bib",
+                ".zenodo.json",
+            ]
+        );
+        assert_eq!(
+            names(match_citation_files(
+                tree,
+                &["**/citation.cff", "paper/*.bib"]
+            )),
+            vec!["citation.CFF", "crates/sub/CITATION.cff", "paper/paper.bib"]
+        );
+        assert!(glob_match("*.bib", "CITATION.BIB"));
+        assert!(!glob_match("*.bib", "docs/citation.bib"));
+        assert!(glob_match("docs/**/?itation.bib", "docs/a/b/citation.bib"));
+    }
+
+    #[test]
+    fn detect_citation_format() {
+        use CitationFormat::*;
+        let bib = "% references\n@software{Pleyer2025,\n    title = {cellular_raza},\n}";
+        let cff = "# comment\ncff-version: 1.2.0\ntitle: cellular_raza\n";
+        let codemeta = r#"{"@context": "https://w3id.org/codemeta/3.0", "name": "x"}"#;
+        let zenodo = r#"{"title": "x", "creators": [{"name": "Pleyer, Jonas"}]}"#;
+        for (filename, text, expected) in [
+            ("docs/v1.2/citation.bib", "", Some(BibLaTeX)),
+            ("paper.final.bib", "", Some(BibLaTeX)),
+            ("refs.BIBTEX", "", Some(BibLaTeX)),
+            ("CITATION.CFF", "", Some(CitationCff)),
+            ("docs/v1.2/CITATION.cff", "", Some(CitationCff)),
+            (".zenodo.json", "", Some(ZenodoJson)),
+            ("docs/.Zenodo.json", "", Some(ZenodoJson)),
+            ("CodeMeta.json", "", Some(CodeMeta)),
+            ("CITATION", cff, Some(CitationCff)),
+            ("CITATION", bib, Some(BibLaTeX)),
+            ("CITATION.md", bib, Some(BibLaTeX)),
+            ("metadata.json", codemeta, Some(CodeMeta)),
+            ("metadata.json", zenodo, Some(ZenodoJson)),
+            ("README.md", "# Contact me @github", None),
+            ("CITATION", "email@example.com {not bib}", None),
+            ("v1.2/README", "", None),
+        ] {
+            assert_eq!(
+                CitationFormat::detect(filename, text),
+                expected,
+                "{filename}: {text}"
+            );
+        }
+    }
+
+    #[test]
+    fn explicit_format_override() {
+        for (pattern, expected) in [
+            ("refs.txt:bib", ("refs.txt", Some(CitationFormat::BibLaTeX))),
+            (
+                "CITATION:cff",
+                ("CITATION", Some(CitationFormat::CitationCff)),
+            ),
+            (
+                "meta.json:codemeta",
+                ("meta.json", Some(CitationFormat::CodeMeta)),
+            ),
+            ("CITATION.cff", ("CITATION.cff", None)),
+        ] {
+            assert_eq!(parse_file_pattern(pattern), expected, "{pattern}");
+        }
+    }
+}
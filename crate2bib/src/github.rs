@@ -7,6 +7,7 @@ async fn response_to_biblatex(
     response: impl Future<Output = Result<reqwest::Response, reqwest::Error>>,
     repository: String,
     filename: String,
+    format: Option<crate::CitationFormat>,
     search_doi: bool,
 ) -> crate::Result<Vec<crate::BibLaTeX>> {
     let text = response.await?.text().await?;
@@ -18,21 +19,43 @@ async fn response_to_biblatex(
         );
         return Ok(vec![]);
     }
-    let chunks: Vec<_> = filename.split(".").collect();
-    let extension = chunks.get(1);
+    text_to_biblatex(client, &text, repository, filename, format, search_doi).await
+}
+
+/// Parses the contents of a citation file depending on its format.
+///
+/// If no `format` is given explicitly, it is detected via [crate::CitationFormat::detect].
+pub(crate) async fn text_to_biblatex(
+    client: reqwest::Client,
+    text: &str,
+    repository: String,
+    filename: String,
+    format: Option<crate::CitationFormat>,
+    search_doi: bool,
+) -> crate::Result<Vec<crate::BibLaTeX>> {
+    use crate::CitationFormat;
     #[cfg(feature = "log")]
-    log::trace!("Checking file extensions in repository");
+    log::trace!("Detecting format of file \"{filename}\"");
+    let format = format
+        .or_else(|| CitationFormat::detect(&filename, text))
+        .ok_or_else(|| {
+            crate::Err::FiletypeUnsupported(format!(
+                "the format of {filename} could not be determined"
+            ))
+        })?;
     let mut results = vec![];
-    match extension {
-        Some(&"bib") => results.push(BibLaTeX::Plain(PlainBibLaTeX {
-            bibliography: biblatex::Bibliography::parse(&text)
-                .map_err(crate::Err::BibLaTeXParsing)?,
+    match format {
+        CitationFormat::BibLaTeX => results.push(BibLaTeX::Plain(PlainBibLaTeX {
+            bibliography: crate::parse_bibliography(text)?,
             repository,
             filename,
+            doi: None,
+            selected: vec![],
+            discovered: false,
         })),
-        Some(&"cff") => {
+        CitationFormat::CitationCff => {
             // Try to obtain plain BibLaTeX entry from doi
-            let citation_cff = citeworks_cff::from_str(&text)?;
+            let citation_cff = citeworks_cff::from_str(text)?;
             if search_doi {
                 if let Some(doi) = citation_cff
                     .preferred_citation
@@ -44,8 +67,12 @@ async fn response_to_biblatex(
                             bibliography: bib,
                             repository,
                             filename,
+                            doi: Some(doi.clone()),
+                            selected: vec![],
+                            discovered: false,
                         })),
                         Ok(None) => (),
+                        #[allow(unused)]
                         Err(e) => {
                             #[cfg(feature = "log")]
                             log::warn!("Received error: \"{e}\" during doi.org request.");
@@ -56,18 +83,69 @@ async fn response_to_biblatex(
 
             results.push(BibLaTeX::CITATIONCFF(citation_cff))
         }
-        None => (),
-        Some(x) => {
-            return Err(crate::Err::FiletypeUnsupported(format!(
-                "the {x} filetype is currently not supported"
-            )))
-        }
+        CitationFormat::ZenodoJson => results.push(BibLaTeX::SoftwareMetadata(
+            crate::SoftwareMetadata::from_zenodo_json(text)?,
+        )),
+        CitationFormat::CodeMeta => results.push(BibLaTeX::SoftwareMetadata(
+            crate::SoftwareMetadata::from_codemeta(text)?,
+        )),
     }
 
     Ok(results)
 }
 
+/// Splits a link to a [github.com](https://github.com) repository into its owner and name
+pub(crate) fn github_owner_repo(repository: &str) -> Option<(String, String)> {
+    let tail = repository.split("github.com").nth(1)?;
+    let mut segments = tail
+        .trim_start_matches([':', '/'])
+        .split(['/', '?', '#'])
+        .filter(|s| !s.is_empty());
+    let owner = segments.next()?;
+    let repo = segments.next()?;
+    Some((owner.to_string(), repo.trim_end_matches(".git").to_string()))
+}
+
+/// Lists the paths of all files of a repository at [github.com](https://github.com) via the
+/// [git trees API](https://docs.github.com/en/rest/git/trees).
+pub async fn github_list_files(
+    client: &reqwest::Client,
+    owner: &str,
+    repo: &str,
+    rev: &str,
+) -> crate::Result<Vec<String>> {
+    let request_url =
+        format!("https://api.github.com/repos/{owner}/{repo}/git/trees/{rev}?recursive=1");
+    #[cfg(feature = "log")]
+    log::trace!("Listing files of repository via \"{request_url}\"");
+    let response = client
+        .get(request_url)
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<serde_json::Value>()
+        .await?;
+    Ok(response
+        .get("tree")
+        .and_then(|t| t.as_array())
+        .map(|tree| {
+            tree.iter()
+                .filter(|x| x.get("type").and_then(|t| t.as_str()) == Some("blob"))
+                .filter_map(|x| x.get("path").and_then(|p| p.as_str()))
+                .map(|p| p.to_string())
+                .collect()
+        })
+        .unwrap_or_default())
+}
+
 /// Searches the repository at [github.com](https://github.com) for citation files
+///
+/// The `filenames` are glob patterns which are matched case-insensitively against all files of the
+/// repository (see [crate::glob_match] and [crate::CITATION_FILE_PATTERNS]).
+/// Files with unsupported types are skipped.
+///
+/// The `branch_name` may be any git revision such as a branch, tag or commit hash.
+/// If it is not specified, the default branch of the repository is used.
 pub async fn github_search_files(
     client: &reqwest::Client,
     repository: &str,
@@ -75,76 +153,418 @@ pub async fn github_search_files(
     branch_name: Option<&str>,
     search_doi: bool,
 ) -> crate::Result<Vec<crate::BibLaTeX>> {
+    Ok(
+        github_find_files(client, repository, filenames, branch_name, search_doi)
+            .await?
+            .0,
+    )
+}
+
+/// Works like [github_search_files] but additionally returns the paths of all matched files
+pub(crate) async fn github_find_files(
+    client: &reqwest::Client,
+    repository: &str,
+    filenames: Vec<&str>,
+    branch_name: Option<&str>,
+    search_doi: bool,
+) -> crate::Result<(Vec<crate::BibLaTeX>, Vec<String>)> {
     // Check if this is Github
     if !repository.contains("github") {
         #[cfg(feature = "log")]
         log::warn!("Cannot query {repository}");
         #[cfg(feature = "log")]
         log::warn!("Currently only github repositories are supported.");
-        return Ok(vec![]);
+        return Ok((vec![], vec![]));
     }
     if filenames.is_empty() {
         #[cfg(feature = "log")]
         log::info!("Did not find any matching filenames");
-        return Ok(Vec::new());
+        return Ok((vec![], vec![]));
     }
 
     let mut results = vec![];
-    let segments: Vec<_> = repository.split("github.com/").collect();
-    if let Some(tail) = segments.get(1) {
-        let segments2: Vec<_> = tail.split("/").collect();
-        let owner = segments2.first();
-        let repo = segments2.get(1);
-        if let (Some(repo), Some(owner)) = (repo, owner) {
-            let request_url = format!("https://api.github.com/repos/{owner}/{repo}");
-
-            // If a branch name was specified we search there and nowhere else
-            let branch_name = if let Some(branch_name) = branch_name {
-                branch_name.to_string()
+    let mut paths = vec![];
+    if let Some((owner, repo)) = github_owner_repo(repository) {
+        let request_url = format!("https://api.github.com/repos/{owner}/{repo}");
+
+        // If a branch name was specified we search there and nowhere else
+        let branch_name = if let Some(branch_name) = branch_name {
+            branch_name.to_string()
+        } else {
+            let respose = client
+                .get(request_url)
+                .send()
+                .await?
+                .json::<serde_json::Value>()
+                .await?;
+
+            if let Some(default_branch) = respose.get("default_branch") {
+                #[cfg(feature = "log")]
+                log::trace!("Determined default branch {default_branch}");
+                default_branch.to_string().replace("\"", "")
             } else {
-                let respose = client
-                    .get(request_url)
-                    .send()
-                    .await?
-                    .json::<serde_json::Value>()
-                    .await?;
-
-                if let Some(default_branch) = respose.get("default_branch") {
-                    #[cfg(feature = "log")]
-                    log::trace!("Determined default branch {default_branch}");
-                    default_branch.to_string().replace("\"", "")
-                } else {
-                    #[cfg(feature = "log")]
-                    log::info!("Automatically chose default branch \"main\"");
-                    "main".to_string()
-                }
-            };
-
-            let request_url_base = format!(
-                "https://raw.githubusercontent.com/\
-                    {owner}/\
-                    {repo}/\
-                    refs/heads/\
-                    {branch_name}"
-            );
-            for filename in filenames.iter() {
-                let rq = format!("{request_url_base}/{filename}");
                 #[cfg(feature = "log")]
-                log::trace!("Requesting github information for file \"{rq}\"");
-                let file_content = client.get(&rq).send();
+                log::info!("Automatically chose default branch \"main\"");
+                "main".to_string()
+            }
+        };
+
+        let found = match github_list_files(client, &owner, &repo, &branch_name).await {
+            Ok(tree) => crate::match_citation_files(tree.iter().map(|x| x.as_str()), &filenames),
+            #[allow(unused)]
+            Err(e) => {
                 #[cfg(feature = "log")]
-                log::trace!("Converting response to BibLaTeX");
-                let r = response_to_biblatex(
-                    client.clone(),
-                    file_content,
-                    repository.to_string(),
-                    filename.to_string(),
-                    search_doi,
-                )
-                .await?;
-                results.extend(r);
+                log::warn!("Could not list files of {repository}: \"{e}\". Using exact names.");
+                filenames
+                    .iter()
+                    .map(|f| crate::parse_file_pattern(f))
+                    .filter(|(f, _)| !f.contains(['*', '?']))
+                    .map(|(f, format)| (f.to_string(), format))
+                    .collect()
+            }
+        };
+        #[cfg(feature = "log")]
+        log::info!("Found citation files {found:?} in {repository}");
+        paths.extend(found.iter().map(|(filename, _)| filename.clone()));
+
+        let request_url_base = format!(
+            "https://raw.githubusercontent.com/\
+                {owner}/\
+                {repo}/\
+                {branch_name}"
+        );
+        results.extend(
+            fetch_citation_files(client, &request_url_base, repository, found, search_doi).await,
+        );
+    }
+    Ok((results, paths))
+}
+
+/// Downloads and parses the given files relative to `request_url_base`.
+///
+/// Files which can not be obtained or parsed are logged and skipped such that a single broken
+/// file does not hide the results of all other sources.
+async fn fetch_citation_files(
+    client: &reqwest::Client,
+    request_url_base: &str,
+    repository: &str,
+    found: Vec<(String, Option<crate::CitationFormat>)>,
+    search_doi: bool,
+) -> Vec<crate::BibLaTeX> {
+    let mut results = vec![];
+    for (filename, format) in found {
+        let rq = format!("{request_url_base}/{filename}");
+        #[cfg(feature = "log")]
+        log::trace!("Requesting github information for file \"{rq}\"");
+        let file_content = client.get(&rq).send();
+        #[cfg(feature = "log")]
+        log::trace!("Converting response to BibLaTeX");
+        #[allow(unused)]
+        let name = filename.clone();
+        match response_to_biblatex(
+            client.clone(),
+            file_content,
+            repository.to_string(),
+            filename,
+            format,
+            search_doi,
+        )
+        .await
+        {
+            Ok(r) => results.extend(r),
+            #[allow(unused)]
+            Err(e) => {
+                #[cfg(feature = "log")]
+                log::warn!("Skipping file \"{name}\": {e}");
             }
         }
     }
-    Ok(results)
+    results
+}
+
+/// Creates a `@software` entry from the metadata of a repository hosted on
+/// [github.com](https://github.com).
+///
+/// The name, description, owner and license are taken from the repository.
+/// If a `rev` is given and it refers to a release, the version and date of this release are used.
+/// If it refers to a commit or branch instead, the date of the commit is used.
+/// Otherwise the latest release is used.
+pub async fn github_repository_metadata(
+    client: &reqwest::Client,
+    repository: &str,
+    rev: Option<&str>,
+) -> crate::Result<crate::BibLaTeXCratesIO> {
+    let (owner, repo) = github_owner_repo(repository).ok_or(crate::NotFoundError(format!(
+        "Cannot query {repository}. Currently only github repositories are supported."
+    )))?;
+    let request_url = format!("https://api.github.com/repos/{owner}/{repo}");
+
+    #[cfg(feature = "log")]
+    log::trace!("Requesting repository metadata from \"{request_url}\"");
+    let info = client
+        .get(&request_url)
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<serde_json::Value>()
+        .await?;
+    let get_str = |value: &serde_json::Value, key: &str| {
+        value
+            .get(key)
+            .and_then(|x| x.as_str())
+            .map(|x| x.to_string())
+    };
+
+    #[cfg(feature = "log")]
+    log::trace!("Requesting release information");
+    let release_url = match rev {
+        Some(rev) => format!("{request_url}/releases/tags/{rev}"),
+        None => format!("{request_url}/releases/latest"),
+    };
+    let release_response = client.get(release_url).send().await?;
+    let release = if release_response.status().is_success() {
+        Some(release_response.json::<serde_json::Value>().await?)
+    } else {
+        #[cfg(feature = "log")]
+        log::info!("Could not find a release for repository {repository}");
+        None
+    };
+
+    let owner_login = info
+        .get("owner")
+        .and_then(|o| get_str(o, "login"))
+        .unwrap_or(owner.clone());
+    let owner_name = match client
+        .get(format!("https://api.github.com/users/{owner_login}"))
+        .send()
+        .await
+    {
+        Ok(r) if r.status().is_success() => r
+            .json::<serde_json::Value>()
+            .await
+            .ok()
+            .and_then(|u| get_str(&u, "name")),
+        _ => None,
+    };
+
+    let tag = release
+        .as_ref()
+        .and_then(|r| get_str(r, "tag_name"))
+        .or(rev.map(|x| x.to_string()));
+    let version = tag.and_then(|t| semver::Version::parse(t.trim_start_matches('v')).ok());
+    let release_date = release
+        .as_ref()
+        .and_then(|r| get_str(r, "published_at"))
+        .and_then(|d| d.parse::<chrono::DateTime<chrono::Utc>>().ok());
+    let date = match (release_date, rev) {
+        (Some(date), _) => Some(date),
+        (None, Some(rev)) => github_commit_date(client, &request_url, rev).await,
+        (None, None) => None,
+    }
+    .or_else(|| {
+        get_str(&info, "pushed_at").and_then(|d| d.parse::<chrono::DateTime<chrono::Utc>>().ok())
+    });
+    let name = get_str(&info, "name").unwrap_or(repo.clone());
+
+    use chrono::Datelike;
+    Ok(crate::BibLaTeXCratesIO {
+        key: format!(
+            "{}{}",
+            owner_name
+                .as_ref()
+                .and_then(|n| n.split(" ").last().map(|x| x.to_string()))
+                .unwrap_or(name.clone()),
+            date.map(|d| format!("{:4}", d.year())).unwrap_or_default()
+        ),
+        work_type: "software".to_string(),
+        author: vec![crate::person_from_name(&owner_name.unwrap_or(owner_login))],
+        title: get_str(&info, "description")
+            .map_or(format!("{{{name}}}"), |x| format!("{{{name}}}: {x}")),
+        url: get_str(&info, "html_url").or(Some(repository.to_string())),
+        doi: None,
+        license: info
+            .get("license")
+            .and_then(|l| get_str(l, "spdx_id"))
+            .filter(|l| l != "NOASSERTION"),
+        version,
+        date,
+        ..Default::default()
+    })
+}
+
+/// Obtains the date of the commit which a revision such as a commit SHA, branch or tag points to
+async fn github_commit_date(
+    client: &reqwest::Client,
+    request_url: &str,
+    rev: &str,
+) -> Option<chrono::DateTime<chrono::Utc>> {
+    #[cfg(feature = "log")]
+    log::trace!("Requesting the date of commit {rev}");
+    let commit = client
+        .get(format!("{request_url}/commits/{rev}"))
+        .send()
+        .await
+        .and_then(|r| r.error_for_status())
+        .ok()?
+        .json::<serde_json::Value>()
+        .await
+        .ok()?;
+    commit
+        .get("commit")
+        .and_then(|c| c.get("committer"))
+        .and_then(|c| c.get("date"))
+        .and_then(|d| d.as_str())
+        .and_then(|d| d.parse().ok())
+}
+
+/// Obtain multiple BibLaTeX entries for a repository without going through crates.io
+///
+/// Searches the repository for the given citation files at the specified revision and creates a
+/// fallback entry from the metadata of the repository (see [github_repository_metadata]).
+/// If the revision is a release tag which was archived on [Zenodo](https://zenodo.org), its DOI is
+/// cited as well.
+/// DOIs found in the `README.md` and `Cargo.toml` are resolved as additional candidates.
+pub async fn get_biblatex_repository(
+    repository: &str,
+    rev: Option<&str>,
+    user_agent: Option<&str>,
+    filenames: Vec<&str>,
+) -> crate::Result<Vec<crate::BibLaTeX>> {
+    let client = crate::build_client(user_agent)?;
+
+    #[cfg(feature = "log")]
+    log::trace!("Obtain entry from repository metadata");
+    let metadata = github_repository_metadata(&client, repository, rev).await?;
+    let query = match rev {
+        Some(tag) => crate::ZenodoQuery::Release { repository, tag },
+        None => crate::ZenodoQuery::Repository(repository),
+    };
+    let zenodo = crate::zenodo_entry(&client, &query, metadata.version.as_ref()).await;
+    #[cfg(feature = "log")]
+    log::trace!("Obtain other entries");
+    let (found, citation_files) =
+        github_find_files(&client, repository, filenames, rev, true).await?;
+    let mut results = vec![BibLaTeX::Repository(crate::BibLaTeXCratesIO {
+        citation_files,
+        ..metadata
+    })];
+    results.extend(zenodo.map(BibLaTeX::Zenodo));
+    results.extend(found);
+    let discovered = match crate::discover_dois(&client, repository, rev).await {
+        Ok(d) => d,
+        #[allow(unused)]
+        Err(e) => {
+            #[cfg(feature = "log")]
+            log::warn!("Could not search {repository} for DOIs: \"{e}\"");
+            vec![]
+        }
+    };
+    let candidates =
+        crate::resolve_discovered_dois(&client, repository, discovered, &results).await;
+    results.extend(candidates);
+    let name = github_owner_repo(repository).map(|(_, repo)| repo);
+    Ok(crate::rank_results(
+        results,
+        name.as_deref(),
+        Some(repository),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_github_owner_repo() {
+        for url in [
+            "https://github.com/jonaspleyer/crate2bib",
+            "https://github.com/jonaspleyer/crate2bib/",
+            "https://github.com/jonaspleyer/crate2bib.git",
+            "git+https://github.com/jonaspleyer/crate2bib.git?rev=0.5.1#abcdef",
+            "git@github.com:jonaspleyer/crate2bib.git",
+            "https://github.com/jonaspleyer/crate2bib/tree/main/crate2bib",
+        ] {
+            assert_eq!(
+                github_owner_repo(url),
+                Some(("jonaspleyer".to_string(), "crate2bib".to_string())),
+                "{url}"
+            );
+        }
+        assert_eq!(github_owner_repo("https://gitlab.com/owner/repo"), None);
+        assert_eq!(github_owner_repo("https://github.com/owner"), None);
+    }
+
+    #[tokio::test]
+    async fn skip_broken_citation_files() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/CITATION.md")
+            .with_body(
+                "# Citation\n\n```bibtex\n@software{broken, author = jane@example.org\n```\n",
+            )
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/CITATION.bib")
+            .with_body("@software{Doe2024, title = {rocket}}")
+            .create_async()
+            .await;
+        let found = vec![
+            (
+                "CITATION.md".to_string(),
+                Some(crate::CitationFormat::BibLaTeX),
+            ),
+            ("CITATION.bib".to_string(), None),
+        ];
+        let results = fetch_citation_files(
+            &reqwest::Client::new(),
+            &server.url(),
+            "https://github.com/owner/repo",
+            found,
+            false,
+        )
+        .await;
+        assert_eq!(results.len(), 1);
+        assert!(matches!(
+            &results[0],
+            BibLaTeX::Plain(p) if p.bibliography.get("Doe2024").is_some()
+        ));
+    }
+
+    #[tokio::test]
+    async fn commit_date_of_revision() {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/repos/owner/repo/commits/0a1b2c3")
+            .with_body(r#"{"commit":{"committer":{"date":"2024-03-05T12:30:00Z"}}}"#)
+            .create_async()
+            .await;
+        let request_url = format!("{}/repos/owner/repo", server.url());
+        let client = reqwest::Client::new();
+        let date = github_commit_date(&client, &request_url, "0a1b2c3").await;
+        assert_eq!(
+            date.map(|d| d.to_string()),
+            Some("2024-03-05 12:30:00 UTC".to_string())
+        );
+        assert_eq!(
+            github_commit_date(&client, &request_url, "unknown").await,
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn cite_repository_directly() -> crate::Result<()> {
+        let results = get_biblatex_repository(
+            "https://github.com/jonaspleyer/cellular_raza",
+            None,
+            Some("crate2bib-testing"),
+            vec!["CITATION.cff"],
+        )
+        .await?;
+        assert!(results.iter().any(|r| matches!(r, BibLaTeX::Repository(_))));
+        assert!(results
+            .iter()
+            .any(|r| matches!(r, BibLaTeX::CITATIONCFF(_))));
+        Ok(())
+    }
 }
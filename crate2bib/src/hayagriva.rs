@@ -0,0 +1,240 @@
+use biblatex::ChunksExt;
+use serde_yaml::{Mapping, Value};
+
+use crate::BibLaTeX;
+
+/// Maps BibLaTeX entry types to [Hayagriva](https://github.com/typst/hayagriva) entry types
+fn hayagriva_type(entry_type: &biblatex::EntryType) -> &'static str {
+    use biblatex::EntryType::*;
+    match entry_type {
+        Software => "repository",
+        Article => "article",
+        Book | MvBook | BookInBook => "book",
+        InBook | InCollection | SuppBook | SuppCollection => "chapter",
+        Collection | MvCollection => "anthology",
+        InProceedings => "article",
+        Proceedings | MvProceedings => "proceedings",
+        Thesis | MastersThesis | PhdThesis => "thesis",
+        Report | TechReport => "report",
+        Online => "web",
+        Patent => "patent",
+        Manual => "reference",
+        Dataset => "repository",
+        _ => "misc",
+    }
+}
+
+fn string(value: impl Into<String>) -> Value {
+    Value::String(value.into())
+}
+
+fn field(entry: &biblatex::Entry, key: &str) -> Option<String> {
+    entry
+        .get(key)
+        .map(|v| crate::unescape_latex_specials(&v.format_verbatim()))
+        .filter(|v| !v.trim().is_empty())
+}
+
+/// Converts a [biblatex::Entry] into the fields of a Hayagriva entry.
+///
+/// Hayagriva has no license field which is why the license is written into the `note`.
+pub fn entry_to_hayagriva(entry: &biblatex::Entry) -> Mapping {
+    let mut map = Mapping::new();
+    let mut insert = |key: &str, value: Option<Value>| {
+        if let Some(value) = value {
+            map.insert(string(key), value);
+        }
+    };
+    insert("type", Some(string(hayagriva_type(&entry.entry_type))));
+    insert("title", field(entry, "title").map(string));
+    let authors: Vec<_> = entry
+        .author()
+        .unwrap_or_default()
+        .iter()
+        .map(|p| {
+            let family = [p.prefix.as_str(), p.name.as_str()]
+                .into_iter()
+                .filter(|x| !x.is_empty())
+                .collect::<Vec<_>>()
+                .join(" ");
+            match (p.given_name.is_empty(), p.suffix.is_empty()) {
+                (true, _) => string(family),
+                (false, true) => string(format!("{family}, {}", p.given_name)),
+                (false, false) => string(format!("{family}, {}, {}", p.given_name, p.suffix)),
+            }
+        })
+        .collect();
+    insert(
+        "author",
+        (!authors.is_empty()).then_some(Value::Sequence(authors)),
+    );
+    insert(
+        "date",
+        field(entry, "date").or(field(entry, "year")).map(string),
+    );
+    insert("version", field(entry, "version").map(string));
+    insert("url", field(entry, "url").map(string));
+    insert("publisher", field(entry, "publisher").map(string));
+    insert("volume", field(entry, "volume").map(string));
+    insert("issue", field(entry, "number").map(string));
+    insert("page-range", field(entry, "pages").map(string));
+    let mut serial = Mapping::new();
+    for (key, hayagriva_key) in [("doi", "doi"), ("isbn", "isbn"), ("issn", "issn")] {
+        if let Some(value) = field(entry, key) {
+            serial.insert(string(hayagriva_key), string(value));
+        }
+    }
+    if let (Some("swh"), Some(eprint)) = (
+        field(entry, "eprinttype").as_deref(),
+        field(entry, "eprint"),
+    ) {
+        serial.insert(string("swhid"), string(eprint));
+    }
+    insert(
+        "serial-number",
+        (!serial.is_empty()).then_some(Value::Mapping(serial)),
+    );
+    let note = [
+        field(entry, "license").map(|l| format!("License: {l}")),
+        field(entry, "note"),
+    ]
+    .into_iter()
+    .flatten()
+    .collect::<Vec<_>>();
+    insert("note", (!note.is_empty()).then(|| string(note.join(". "))));
+    let parent_title = field(entry, "journaltitle")
+        .or(field(entry, "journal"))
+        .or(field(entry, "booktitle"));
+    if let Some(title) = parent_title {
+        let parent_type = match entry.entry_type {
+            biblatex::EntryType::InProceedings => "proceedings",
+            biblatex::EntryType::Article => "periodical",
+            _ => "book",
+        };
+        let mut parent = Mapping::new();
+        parent.insert(string("type"), string(parent_type));
+        parent.insert(string("title"), string(title));
+        map.insert(string("parent"), Value::Mapping(parent));
+    }
+    map
+}
+
+/// Serializes entries into a [Hayagriva](https://github.com/typst/hayagriva) YAML bibliography
+/// as used by [Typst](https://typst.app)
+///
+/// All entries are written into a single mapping.
+/// Entries whose key was already used such as the entries from crates.io and a `CITATION.cff`
+/// file are stored under the key with an added suffix as in `Pleyer2025_2`.
+pub fn entries_to_hayagriva<'a>(
+    entries: impl IntoIterator<Item = &'a biblatex::Entry>,
+) -> crate::Result<String> {
+    let mut map = Mapping::new();
+    for entry in entries {
+        let key = std::iter::once(entry.key.clone())
+            .chain((2..).map(|n| format!("{}_{n}", entry.key)))
+            .find(|k| !map.contains_key(k.as_str()))
+            .unwrap();
+        map.insert(string(key), Value::Mapping(entry_to_hayagriva(entry)));
+    }
+    Ok(serde_yaml::to_string(&map)?)
+}
+
+impl BibLaTeX {
+    /// Formats the entry as a [Hayagriva](https://github.com/typst/hayagriva) YAML bibliography
+    pub fn to_hayagriva(&self) -> crate::Result<String> {
+        entries_to_hayagriva(&crate::result_entries(self)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn software_to_hayagriva() -> crate::Result<()> {
+        let entry = BibLaTeX::CratesIO(crate::BibLaTeXCratesIO {
+            key: "Pleyer2025".to_string(),
+            work_type: "software".to_string(),
+            author: vec![
+                crate::person_from_name("Jonas Pleyer"),
+                crate::person_from_name("Christian Fleck"),
+            ],
+            title: "{cellular_raza}: Cellular Agent-based Modeling".to_string(),
+            url: Some("https://github.com/jonaspleyer/cellular_raza".to_string()),
+            doi: Some("10.5281/zenodo.15549".to_string()),
+            license: Some("GPL-2.0".to_string()),
+            version: Some(semver::Version::new(0, 2, 4)),
+            date: chrono::NaiveDate::from_ymd_opt(2025, 6, 1)
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+                .map(|d| d.and_utc()),
+            ..Default::default()
+        });
+        let expected = "\
+Pleyer2025:
+  type: repository
+  title: 'cellular_raza: Cellular Agent-based Modeling'
+  author:
+  - Pleyer, Jonas
+  - Fleck, Christian
+  date: 2025-06-01
+  version: 0.2.4
+  url: https://github.com/jonaspleyer/cellular_raza
+  serial-number:
+    doi: 10.5281/zenodo.15549
+  note: 'License: GPL-2.0'
+";
+        assert_eq!(entry.to_hayagriva()?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn article_to_hayagriva() -> crate::Result<()> {
+        let bibliography = biblatex::Bibliography::parse(
+            "@article{Pleyer_2025,
+                author = {Pleyer, Jonas and Fleck, Christian},
+                title = {cellular\\_raza: Cellular Agent-based Modeling from a Clean Slate},
+                journal = {Journal of Open Source Software},
+                volume = {10}, number = {110}, pages = {7723}, year = {2025},
+            }",
+        )?;
+        // Parsed titles keep their escaped characters which are removed again
+        let bibliography = crate::parse_bibliography(&crate::entries_to_biblatex_string(
+            bibliography.iter(),
+            crate::Encoding::Utf8,
+        ))?;
+        let yaml = entries_to_hayagriva(bibliography.iter())?;
+        let parsed: Value = serde_yaml::from_str(&yaml)?;
+        let entry = &parsed["Pleyer_2025"];
+        assert_eq!(entry["type"], string("article"));
+        assert_eq!(
+            entry["title"],
+            string("cellular_raza: Cellular Agent-based Modeling from a Clean Slate")
+        );
+        assert_eq!(entry["date"], string("2025"));
+        assert_eq!(entry["issue"], string("110"));
+        assert_eq!(entry["parent"]["type"], string("periodical"));
+        assert_eq!(
+            entry["parent"]["title"],
+            string("Journal of Open Source Software")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn deduplicate_hayagriva_keys() -> crate::Result<()> {
+        let bibliography = biblatex::Bibliography::parse(
+            "@software{Pleyer2025, title = {cellular\\_raza}}
+            @software{Pleyer2025_2, title = {cellular\\_raza-core}}",
+        )?;
+        let entries: Vec<_> = bibliography
+            .iter()
+            .chain(bibliography.iter().take(1))
+            .cloned()
+            .collect();
+        let yaml = entries_to_hayagriva(&entries)?;
+        let parsed: Mapping = serde_yaml::from_str(&yaml)?;
+        let keys: Vec<_> = parsed.keys().filter_map(|k| k.as_str()).collect();
+        assert_eq!(keys, vec!["Pleyer2025", "Pleyer2025_2", "Pleyer2025_3"]);
+        Ok(())
+    }
+}
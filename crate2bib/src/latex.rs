@@ -0,0 +1,239 @@
+use serde::{Deserialize, Serialize};
+
+/// Determines how non-ASCII characters are written into BibLaTeX fields
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Encoding {
+    /// Keep characters as raw UTF-8 which is supported by `biber` and modern LaTeX engines
+    #[default]
+    Utf8,
+    /// Replace accented characters by LaTeX commands such as `{\'{e}}` for `é`
+    Latex,
+}
+
+impl std::str::FromStr for Encoding {
+    type Err = crate::Err;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "utf8" | "utf-8" => Ok(Encoding::Utf8),
+            "latex" | "ascii" => Ok(Encoding::Latex),
+            x => Err(crate::Err::InvalidValue(format!(
+                "the {x} encoding is currently not supported"
+            ))),
+        }
+    }
+}
+
+/// Accent commands together with the accented characters and their base letters
+const ACCENTS: &[(char, &str, &str)] = &[
+    ('`', "àèìòùÀÈÌÒÙ", "aeiouAEIOU"),
+    ('\'', "áéíóúýćńśźĺŕÁÉÍÓÚÝĆŃŚŹĹŔ", "aeiouycnszlrAEIOUYCNSZLR"),
+    ('^', "âêîôûĉĝĥĵŝŵŷÂÊÎÔÛĈĜĤĴŜŴŶ", "aeioucghjswyAEIOUCGHJSWY"),
+    ('~', "ãñõĩũÃÑÕĨŨ", "anoiuANOIU"),
+    ('"', "äëïöüÿÄËÏÖÜŸ", "aeiouyAEIOUY"),
+    ('=', "āēīōūĀĒĪŌŪ", "aeiouAEIOU"),
+    ('u', "ăğŭĂĞŬ", "aguAGU"),
+    ('.', "żėŻĖİ", "zeZEI"),
+    ('c', "çşţģķļņÇŞŢĢĶĻŅ", "cstgklnCSTGKLN"),
+    ('v', "čďěňřšťžľČĎĚŇŘŠŤŽĽ", "cdenrstzlCDENRSTZL"),
+    ('r', "åůÅŮ", "auAU"),
+    ('H', "őűŐŰ", "ouOU"),
+    ('k', "ąęįųĄĘĮŲ", "aeiuAEIU"),
+];
+
+/// Characters which have a dedicated LaTeX command
+const SPECIAL_LETTERS: &[(char, &str)] = &[
+    ('ß', "\\ss"),
+    ('æ', "\\ae"),
+    ('Æ', "\\AE"),
+    ('ø', "\\o"),
+    ('Ø', "\\O"),
+    ('œ', "\\oe"),
+    ('Œ', "\\OE"),
+    ('ł', "\\l"),
+    ('Ł', "\\L"),
+    ('ı', "\\i"),
+    ('đ', "\\dj"),
+    ('Đ', "\\DJ"),
+];
+
+fn latex_accent(c: char) -> Option<String> {
+    if let Some((_, command)) = SPECIAL_LETTERS.iter().find(|(x, _)| *x == c) {
+        return Some(format!("{{{command}}}"));
+    }
+    ACCENTS.iter().find_map(|(accent, accented, base)| {
+        let n = accented.chars().position(|x| x == c)?;
+        let base = base.chars().nth(n)?;
+        Some(format!("{{\\{accent}{{{base}}}}}"))
+    })
+}
+
+/// Finds all braces which have a matching partner
+fn balanced_braces(chars: &[char]) -> Vec<bool> {
+    let mut balanced = vec![false; chars.len()];
+    let mut open = vec![];
+    for (n, c) in chars.iter().enumerate() {
+        match c {
+            '{' => open.push(n),
+            '}' => {
+                if let Some(m) = open.pop() {
+                    balanced[m] = true;
+                    balanced[n] = true;
+                }
+            }
+            _ => (),
+        }
+    }
+    balanced
+}
+
+fn escape_char(c: char, encoding: Encoding, out: &mut String) {
+    match c {
+        '&' | '%' | '$' | '#' | '_' | '{' | '}' => {
+            out.push('\\');
+            out.push(c);
+        }
+        '~' => out.push_str("\\textasciitilde{}"),
+        '^' => out.push_str("\\textasciicircum{}"),
+        '\\' => out.push_str("\\textbackslash{}"),
+        c if !c.is_ascii() && encoding == Encoding::Latex => match latex_accent(c) {
+            Some(s) => out.push_str(&s),
+            None => out.push(c),
+        },
+        c => out.push(c),
+    }
+}
+
+/// Words which contain uppercase letters after their first character such as `GPU` or `OpenGL`
+fn is_acronym(word: &str) -> bool {
+    word.chars().skip(1).any(|c| c.is_uppercase())
+}
+
+fn escape(text: &str, encoding: Encoding, protect: bool) -> String {
+    let chars: Vec<_> = text.chars().collect();
+    let balanced = balanced_braces(&chars);
+    let mut out = String::with_capacity(text.len());
+    let mut depth = 0;
+    let mut n = 0;
+    while n < chars.len() {
+        let c = chars[n];
+        match c {
+            '{' | '}' if balanced[n] => {
+                depth = if c == '{' { depth + 1 } else { depth - 1 };
+                out.push(c);
+            }
+            c if protect && depth == 0 && c.is_alphanumeric() => {
+                let len = chars[n..]
+                    .iter()
+                    .take_while(|c| c.is_alphanumeric())
+                    .count();
+                let word: String = chars[n..n + len].iter().collect();
+                let acronym = is_acronym(&word);
+                if acronym {
+                    out.push('{');
+                }
+                word.chars()
+                    .for_each(|c| escape_char(c, encoding, &mut out));
+                if acronym {
+                    out.push('}');
+                }
+                n += len;
+                continue;
+            }
+            c => escape_char(c, encoding, &mut out),
+        }
+        n += 1;
+    }
+    out
+}
+
+/// Escapes characters with a special meaning in LaTeX such as `&`, `%`, `_` or `#`.
+///
+/// Balanced braces are kept since they group text intentionally while unbalanced braces are
+/// escaped.
+/// Non-ASCII characters are replaced by LaTeX commands if [Encoding::Latex] is chosen.
+/// Fields such as `url` or `doi` are verbatim in BibLaTeX and must not be escaped.
+pub fn escape_latex(text: &str, encoding: Encoding) -> String {
+    escape(text, encoding, false)
+}
+
+/// Replaces non-ASCII characters by LaTeX commands if [Encoding::Latex] is chosen while leaving
+/// everything else unchanged.
+///
+/// This is used for text which is already valid LaTeX such as entries of a `.bib` file.
+pub fn encode_non_ascii(text: &str, encoding: Encoding) -> String {
+    match encoding {
+        Encoding::Utf8 => text.to_string(),
+        Encoding::Latex => text
+            .chars()
+            .map(|c| match c {
+                c if !c.is_ascii() => latex_accent(c).unwrap_or(c.to_string()),
+                c => c.to_string(),
+            })
+            .collect(),
+    }
+}
+
+/// Removes the backslash from escaped special characters such as `\&` or `\_` for output
+/// formats which are not LaTeX
+pub(crate) fn unescape_latex_specials(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('\\', Some('&' | '%' | '$' | '#' | '_')) => (),
+            (c, _) => out.push(c),
+        }
+    }
+    out
+}
+
+/// Escapes a title like [escape_latex] and additionally protects acronyms and names such as
+/// `GPU` or `OpenGL` with braces such that bibliography styles do not change their case.
+pub fn escape_latex_title(text: &str, encoding: Encoding) -> String {
+    escape(text, encoding, true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escape_special_characters() {
+        for (text, expected) in [
+            ("Fast & safe", "Fast \\& safe"),
+            (
+                "100% Rust_code #1 for $5",
+                "100\\% Rust\\_code \\#1 for \\$5",
+            ),
+            (
+                "~/.cargo and x^2",
+                "\\textasciitilde{}/.cargo and x\\textasciicircum{}2",
+            ),
+            ("C:\\path", "C:\\textbackslash{}path"),
+            ("{balanced} but } not {", "{balanced} but \\} not \\{"),
+            ("Müller", "Müller"),
+        ] {
+            assert_eq!(escape_latex(text, Encoding::Utf8), expected);
+        }
+        assert_eq!(
+            escape_latex("Jürgen Øster, Łukasz Dvořák", Encoding::Latex),
+            "J{\\\"{u}}rgen {\\O}ster, {\\L}ukasz Dvo{\\v{r}}{\\'{a}}k"
+        );
+    }
+
+    #[test]
+    fn protect_acronyms_in_title() {
+        assert_eq!(
+            escape_latex_title(
+                "{cellular_raza}: Agent-based GPU simulations with OpenGL",
+                Encoding::Utf8
+            ),
+            "{cellular\\_raza}: Agent-based {GPU} simulations with {OpenGL}"
+        );
+        assert_eq!(
+            escape_latex_title("{An {OpenGL} crate}", Encoding::Utf8),
+            "{An {OpenGL} crate}"
+        );
+    }
+}
@@ -6,40 +6,85 @@
 #![deny(missing_docs)]
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
+mod advisories;
+mod aggregate;
+mod batch;
+mod cff;
 mod crates_io;
+mod discovery;
 mod doi;
+mod entry;
+mod files;
 mod github;
+mod hayagriva;
+mod latex;
+mod lockfile;
+mod merge;
+mod metadata;
 #[cfg_attr(docsrs, doc(cfg(feature = "pyo3")))]
 #[cfg(feature = "pyo3")]
 mod python;
+mod registry;
+mod report;
+mod resolve;
+mod scan;
+mod selection;
+mod style;
+mod swh;
 mod types;
+mod update;
+mod zenodo;
 
+pub use advisories::*;
+pub use aggregate::*;
+pub use batch::*;
+pub use cff::*;
 pub use crates_io::*;
+pub use discovery::*;
 pub use doi::*;
+pub use entry::*;
+pub use files::*;
 pub use github::*;
+pub use hayagriva::*;
+pub use latex::*;
+pub use lockfile::*;
+pub use merge::*;
+pub use metadata::*;
+pub use registry::*;
+pub use report::*;
+pub use resolve::*;
+pub use scan::*;
+pub use selection::*;
+pub use style::*;
+pub use swh::*;
 pub use types::*;
+pub use update::*;
+pub use zenodo::*;
+
+/// Re-export of the [biblatex] crate whose entries are used throughout the public API
+pub use biblatex;
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use biblatex::ChunksExt;
 
     #[tokio::test]
     async fn obtain_from_doi_org() {
+        // The day of the issue date depends on the metadata of the registration agency which is
+        // why only the year and month are compared.
         let expected = r#"@article{Pleyer_2025,
-author = {Pleyer, Jonas and Fleck, Christian},
-doi = {10.21105/joss.07723},
-issn = {2475-9066},
-journaltitle = {Journal of Open Source Software},
-month = {June},
-number = {110},
-pages = {7723},
-publisher = {The Open Journal},
-title = {cellular\_raza: Cellular Agent-based Modeling from a Clean Slate},
-url = {http://dx.doi.org/10.21105/joss.07723},
-volume = {10},
-year = {2025},
-}
-"#;
+    author = {Pleyer, Jonas and Fleck, Christian},
+    title = {cellular\_raza: Cellular Agent-based Modeling from a Clean Slate},
+    journaltitle = {Journal of Open Source Software},
+    volume = {10},
+    number = {110},
+    pages = {7723},
+    publisher = {The Open Journal},
+    url = {http://dx.doi.org/10.21105/joss.07723},
+    doi = {10.21105/joss.07723},
+    issn = {2475-9066},
+}"#;
 
         let results = get_biblatex(
             "cellular_raza",
@@ -51,10 +96,17 @@ year = {2025},
         .await
         .unwrap();
 
+        let mut found = false;
         for r in results.iter() {
-            if let BibLaTeX::Plain(_) = r {
-                assert_eq!(expected, format!("{r}"));
+            if let BibLaTeX::Plain(plain) = r {
+                let mut plain = plain.clone();
+                let entry = plain.bibliography.iter_mut().next().unwrap();
+                let date = entry.fields.remove("date").unwrap().format_verbatim();
+                assert!(date.starts_with("2025-06"), "{date}");
+                assert_eq!(expected, format!("{}", BibLaTeX::Plain(plain)));
+                found = true;
             }
         }
+        assert!(found);
     }
 }
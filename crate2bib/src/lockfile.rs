@@ -0,0 +1,670 @@
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{BibLaTeX, BibLaTeXCratesIO};
+
+/// Describes where a package listed in a `Cargo.lock` file originates from
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum PackageSource {
+    /// The package was obtained from the registry with the given index
+    Registry(String),
+    /// The package was obtained from a git repository
+    Git {
+        /// Link to the repository
+        repository: String,
+        /// The branch, tag or revision which was specified in the manifest
+        reference: Option<String>,
+        /// The commit at which the dependency is pinned
+        commit: Option<String>,
+    },
+    /// The package is a path dependency or a member of the workspace
+    Path,
+}
+
+impl PackageSource {
+    /// Parses the `source` field of a package in a `Cargo.lock` file.
+    ///
+    /// Git sources are recorded as `git+https://…?rev=…#sha` while path dependencies have no
+    /// source at all.
+    pub fn parse(source: Option<&str>) -> Self {
+        let Some(source) = source else {
+            return PackageSource::Path;
+        };
+        if let Some(git) = source.strip_prefix("git+") {
+            let (url, commit) = match git.split_once('#') {
+                Some((url, commit)) => (url, Some(commit.to_string())),
+                None => (git, None),
+            };
+            let (repository, reference) = match url.split_once('?') {
+                Some((repository, query)) => (
+                    repository,
+                    query.split('&').find_map(|q| {
+                        let (k, v) = q.split_once('=')?;
+                        matches!(k, "rev" | "tag" | "branch").then(|| v.to_string())
+                    }),
+                ),
+                None => (url, None),
+            };
+            PackageSource::Git {
+                repository: repository.to_string(),
+                reference,
+                commit,
+            }
+        } else {
+            let index = source
+                .strip_prefix("registry+")
+                .unwrap_or(source)
+                .to_string();
+            PackageSource::Registry(index)
+        }
+    }
+
+    /// Checks if this source refers to [crates.io](https://crates.io)
+    pub fn is_crates_io(&self) -> bool {
+        match self {
+            PackageSource::Registry(index) => {
+                index.contains("github.com/rust-lang/crates.io-index")
+                    || index.contains("index.crates.io")
+            }
+            _ => false,
+        }
+    }
+}
+
+/// A single package as listed in a `Cargo.lock` file
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct LockedPackage {
+    /// Name of the package
+    pub name: String,
+    /// The exact version which is locked
+    pub version: semver::Version,
+    /// Where the package originates from
+    pub source: PackageSource,
+}
+
+#[derive(Deserialize)]
+struct CargoLock {
+    #[serde(default)]
+    package: Vec<CargoLockPackage>,
+}
+
+#[derive(Deserialize)]
+struct CargoLockPackage {
+    name: String,
+    version: String,
+    source: Option<String>,
+}
+
+/// Parses the contents of a `Cargo.lock` file
+pub fn parse_cargo_lock(contents: &str) -> crate::Result<Vec<LockedPackage>> {
+    let lock: CargoLock = toml::from_str(contents)?;
+    lock.package
+        .into_iter()
+        .map(|p| {
+            Ok(LockedPackage {
+                version: semver::Version::parse(&p.version)?,
+                source: PackageSource::parse(p.source.as_deref()),
+                name: p.name,
+            })
+        })
+        .collect()
+}
+
+/// Searches the given directory recursively for the manifest of the package with the given name.
+///
+/// Hidden directories and `target` directories are skipped and the search descends at most
+/// four levels below `root`.
+/// Returns the directory which contains the `Cargo.toml` file.
+pub fn find_local_package(root: &Path, name: &str) -> Option<PathBuf> {
+    fn visit(dir: &Path, name: &str, depth: usize) -> Option<PathBuf> {
+        let manifest = dir.join("Cargo.toml");
+        if let Ok(contents) = std::fs::read_to_string(&manifest) {
+            if let Ok(table) = contents.parse::<toml::Table>() {
+                let found = table
+                    .get("package")
+                    .and_then(|p| p.get("name"))
+                    .and_then(|n| n.as_str());
+                if found == Some(name) {
+                    return Some(dir.to_path_buf());
+                }
+            }
+        }
+        if depth == 0 {
+            return None;
+        }
+        let mut entries: Vec<_> = std::fs::read_dir(dir)
+            .ok()?
+            .filter_map(|e| e.ok())
+            .map(|e| e.path())
+            .filter(|p| p.is_dir())
+            .filter(|p| {
+                p.file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| !n.starts_with('.') && n != "target")
+            })
+            .collect();
+        entries.sort();
+        entries
+            .into_iter()
+            .find_map(|dir| visit(&dir, name, depth - 1))
+    }
+    visit(root, name, 4)
+}
+
+/// Creates a [BibLaTeXCratesIO] entry from the contents of a `Cargo.toml` file.
+///
+/// Fields which are inherited from the workspace (eg. `version.workspace = true`) are looked up in
+/// the `[workspace.package]` table of the optionally supplied workspace manifest.
+pub fn manifest_to_biblatex(
+    manifest: &str,
+    workspace_manifest: Option<&str>,
+) -> crate::Result<BibLaTeXCratesIO> {
+    let manifest = manifest.parse::<toml::Table>()?;
+    let workspace = workspace_manifest
+        .map(|w| w.parse::<toml::Table>())
+        .transpose()?;
+    let workspace_package = workspace
+        .as_ref()
+        .and_then(|w| w.get("workspace"))
+        .and_then(|w| w.get("package"));
+    let package = manifest.get("package").ok_or(crate::NotFoundError(
+        "manifest has no [package] table".into(),
+    ))?;
+    let get = |key: &str| -> Option<toml::Value> {
+        let value = package.get(key)?;
+        if value.get("workspace").and_then(|w| w.as_bool()) == Some(true) {
+            workspace_package?.get(key).cloned()
+        } else {
+            Some(value.clone())
+        }
+    };
+    let get_str = |key: &str| get(key).and_then(|v| v.as_str().map(|x| x.to_string()));
+
+    let name = get_str("name").ok_or(crate::NotFoundError("package has no name".into()))?;
+    let authors: Vec<String> = get("authors")
+        .and_then(|a| {
+            a.as_array().map(|a| {
+                a.iter()
+                    .filter_map(|x| x.as_str())
+                    .map(|x| x.split('<').next().unwrap_or(x).trim().to_string())
+                    .collect()
+            })
+        })
+        .unwrap_or_default();
+
+    Ok(BibLaTeXCratesIO {
+        key: authors
+            .first()
+            .and_then(|a| a.split(" ").last().map(|x| x.to_string()))
+            .unwrap_or(name.clone()),
+        work_type: "software".to_string(),
+        author: authors.iter().map(|a| crate::person_from_name(a)).collect(),
+        title: get_str("description").map_or(format!("{{{name}}}"), |x| format!("{{{name}}}: {x}")),
+        url: get_str("repository").or(get_str("homepage")),
+        doi: None,
+        license: get_str("license"),
+        version: get_str("version").and_then(|v| semver::Version::parse(&v).ok()),
+        date: None,
+        ..Default::default()
+    })
+}
+
+/// Dependencies of a `Cargo.toml` file (see [manifest_dependencies])
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ManifestDependencies {
+    /// Dependencies which can be cited from [crates.io](https://crates.io)
+    pub requests: Vec<crate::CrateRequest>,
+    /// Names of path, git and alternative registry dependencies which are not published on
+    /// [crates.io](https://crates.io).
+    /// These can be cited from the `Cargo.lock` file (see [get_biblatex_lockfile]).
+    pub skipped: Vec<String>,
+}
+
+/// Lists the dependencies of a `Cargo.toml` file together with their version requirements.
+///
+/// Renamed dependencies are cited by the name of their package and dependencies inherited from
+/// the workspace (`serde.workspace = true`) are looked up in the `[workspace.dependencies]` table
+/// of the optionally supplied workspace manifest.
+/// Platform-specific dependencies in `[target.*.dependencies]` tables are included.
+/// Path and git dependencies without a version as well as dependencies from other registries are
+/// skipped since a crate with the same name on [crates.io](https://crates.io) may be unrelated.
+/// If `dev` is set, `[dev-dependencies]` and `[build-dependencies]` are included as well.
+pub fn manifest_dependencies(
+    manifest: &str,
+    workspace_manifest: Option<&str>,
+    dev: bool,
+) -> crate::Result<ManifestDependencies> {
+    let manifest = manifest.parse::<toml::Table>()?;
+    let workspace = workspace_manifest
+        .map(|w| w.parse::<toml::Table>())
+        .transpose()?;
+    let workspace_dependencies = workspace
+        .as_ref()
+        .and_then(|w| w.get("workspace"))
+        .and_then(|w| w.get("dependencies"));
+    let kinds: &[&str] = if dev {
+        &["dependencies", "dev-dependencies", "build-dependencies"]
+    } else {
+        &["dependencies"]
+    };
+    let targets = manifest
+        .get("target")
+        .and_then(|t| t.as_table())
+        .into_iter()
+        .flat_map(|t| t.values());
+    let tables: Vec<_> = std::iter::once(&manifest)
+        .chain(targets.filter_map(|t| t.as_table()))
+        .flat_map(|t| kinds.iter().filter_map(|kind| t.get(*kind)))
+        .filter_map(|d| d.as_table())
+        .collect();
+
+    let mut dependencies = ManifestDependencies::default();
+    for (name, value) in tables.into_iter().flatten() {
+        let mut value = value.clone();
+        if value.get("workspace").and_then(|w| w.as_bool()) == Some(true) {
+            if let Some(inherited) = workspace_dependencies.and_then(|w| w.get(name)) {
+                value = inherited.clone();
+            }
+        }
+        let (package, version) = match &value {
+            toml::Value::String(version) => (name.as_str(), Some(version.clone())),
+            table => (
+                table
+                    .get("package")
+                    .and_then(|p| p.as_str())
+                    .unwrap_or(name),
+                table
+                    .get("version")
+                    .and_then(|v| v.as_str())
+                    .map(|v| v.to_string()),
+            ),
+        };
+        let local = value.get("path").is_some() || value.get("git").is_some();
+        let published = value.get("registry").is_none() && (version.is_some() || !local);
+        if !published {
+            if !dependencies.skipped.iter().any(|s| s == package) {
+                dependencies.skipped.push(package.to_string());
+            }
+        } else if !dependencies.requests.iter().any(|r| r.name == package) {
+            dependencies.requests.push(crate::CrateRequest {
+                name: package.to_string(),
+                version,
+            });
+        }
+    }
+    Ok(dependencies)
+}
+
+/// Searches the given directories on disk for citation files matching the glob patterns in
+/// `filenames`.
+///
+/// Files found in earlier directories take precedence over files with the same path in later ones.
+pub(crate) async fn local_search_files(
+    client: &reqwest::Client,
+    directories: &[&Path],
+    repository: &str,
+    filenames: &[&str],
+    search_doi: bool,
+) -> crate::Result<Vec<BibLaTeX>> {
+    let mut results = vec![];
+    let mut seen = vec![];
+    for dir in directories {
+        let files = crate::list_local_files(dir);
+        for (filename, format) in
+            crate::match_citation_files(files.iter().map(|x| x.as_str()), filenames)
+        {
+            if seen.contains(&filename) {
+                continue;
+            }
+            let text = match std::fs::read_to_string(dir.join(&filename)) {
+                Ok(text) => text,
+                #[allow(unused)]
+                Err(e) => {
+                    #[cfg(feature = "log")]
+                    log::warn!("Skipping file \"{filename}\": {e}");
+                    continue;
+                }
+            };
+            #[cfg(feature = "log")]
+            log::trace!("Found local file \"{filename}\"");
+            seen.push(filename.clone());
+            #[allow(unused)]
+            let name = filename.clone();
+            match crate::text_to_biblatex(
+                client.clone(),
+                &text,
+                repository.to_string(),
+                filename,
+                format,
+                search_doi,
+            )
+            .await
+            {
+                Ok(r) => results.extend(r),
+                #[allow(unused)]
+                Err(e) => {
+                    #[cfg(feature = "log")]
+                    log::warn!("Skipping file \"{name}\": {e}");
+                }
+            }
+        }
+    }
+    Ok(results)
+}
+
+/// Obtain BibLaTeX entries for a single package of a `Cargo.lock` file.
+///
+/// - Packages from [crates.io](https://crates.io) or other sparse registries are cited at their
+///   exact locked version.
+/// - Git dependencies are cited at their pinned commit by searching the repository for citation
+///   files.
+/// - Path dependencies are searched for inside the `workspace_root`.
+///   They are cited from their local `Cargo.toml` and citation files on disk.
+pub async fn get_biblatex_locked_package(
+    package: &LockedPackage,
+    workspace_root: &Path,
+    user_agent: Option<&str>,
+    filenames: Vec<&str>,
+) -> crate::Result<Vec<BibLaTeX>> {
+    let client = crate::build_client(user_agent)?;
+    let crates_io_client = crate::build_crates_io_client(&client);
+    get_biblatex_locked_package_with_clients(
+        package,
+        workspace_root,
+        user_agent,
+        &client,
+        &crates_io_client,
+        filenames,
+    )
+    .await
+}
+
+/// Same as [get_biblatex_locked_package] but reuses the given clients such that the rate limit of
+/// [crates.io](https://crates.io) is respected across multiple packages.
+async fn get_biblatex_locked_package_with_clients(
+    package: &LockedPackage,
+    workspace_root: &Path,
+    user_agent: Option<&str>,
+    client: &reqwest::Client,
+    crates_io_client: &crate::CratesIoClient,
+    filenames: Vec<&str>,
+) -> crate::Result<Vec<BibLaTeX>> {
+    match &package.source {
+        PackageSource::Registry(index) => {
+            let version = format!("={}", package.version);
+            if package.source.is_crates_io() {
+                crate::get_biblatex_with_clients(
+                    &package.name,
+                    Some(&version),
+                    &crate::ResolveOptions::default(),
+                    client,
+                    crates_io_client,
+                    None,
+                    filenames,
+                )
+                .await
+            } else if index.starts_with("sparse+") {
+                crate::get_biblatex_registry(
+                    &package.name,
+                    Some(&version),
+                    &crate::ResolveOptions::default(),
+                    &crate::Registry::from_url(index),
+                    user_agent,
+                    None,
+                    filenames,
+                )
+                .await
+            } else {
+                Err(crate::NotFoundError(format!(
+                    "registry {index} of package {} does not use the sparse protocol",
+                    package.name
+                ))
+                .into())
+            }
+        }
+        PackageSource::Git {
+            repository,
+            reference,
+            commit,
+        } => {
+            let rev = commit.as_deref().or(reference.as_deref());
+            let mut results =
+                crate::get_biblatex_repository(repository, rev, user_agent, filenames).await?;
+            for result in results.iter_mut() {
+                if let BibLaTeX::Repository(b) = result {
+                    b.version = b.version.take().or(Some(package.version.clone()));
+                }
+            }
+            Ok(results)
+        }
+        PackageSource::Path => {
+            let directory = find_local_package(workspace_root, &package.name).ok_or(
+                crate::NotFoundError(format!(
+                    "could not find package {} in {}",
+                    package.name,
+                    workspace_root.display()
+                )),
+            )?;
+            #[cfg(feature = "log")]
+            log::trace!("Reading local manifest in {}", directory.display());
+            let manifest = std::fs::read_to_string(directory.join("Cargo.toml"))?;
+            let workspace_manifest =
+                std::fs::read_to_string(workspace_root.join("Cargo.toml")).ok();
+            let mut entry = manifest_to_biblatex(&manifest, workspace_manifest.as_deref())?;
+            entry.version = entry.version.or(Some(package.version.clone()));
+
+            let repository = entry.url.clone().unwrap_or(directory.display().to_string());
+            let mut results = local_search_files(
+                client,
+                &[&directory, workspace_root],
+                &repository,
+                &filenames,
+                true,
+            )
+            .await?;
+            results.push(BibLaTeX::Repository(entry));
+            crate::select_cited_entries(&mut results, Some(&package.name), &repository);
+            results.sort_by_key(|x| u8::MAX - x.priority());
+            Ok(results)
+        }
+    }
+}
+
+/// Obtain BibLaTeX entries for every package listed in the given `Cargo.lock` file.
+///
+/// Path dependencies are searched for in the directory which contains the lockfile.
+/// Failures of individual packages are reported alongside the package and do not abort the
+/// remaining packages.
+/// See [get_biblatex_locked_package].
+pub async fn get_biblatex_lockfile(
+    lockfile: &Path,
+    user_agent: Option<&str>,
+    filenames: Vec<&str>,
+) -> crate::Result<Vec<(LockedPackage, crate::Result<Vec<BibLaTeX>>)>> {
+    let contents = std::fs::read_to_string(lockfile)?;
+    let packages = parse_cargo_lock(&contents)?;
+    let workspace_root = lockfile.parent().unwrap_or(Path::new("."));
+    let client = crate::build_client(user_agent)?;
+    let crates_io_client = crate::build_crates_io_client(&client);
+
+    let mut results = vec![];
+    for package in packages {
+        #[cfg(feature = "log")]
+        log::trace!("Obtaining entries for package {}", package.name);
+        let r = get_biblatex_locked_package_with_clients(
+            &package,
+            workspace_root,
+            user_agent,
+            &client,
+            &crates_io_client,
+            filenames.clone(),
+        )
+        .await;
+        results.push((package, r));
+    }
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CARGO_LOCK: &str = r#"
+version = 4
+
+[[package]]
+name = "crate2bib"
+version = "0.5.1"
+
+[[package]]
+name = "serde"
+version = "1.0.219"
+source = "registry+https://github.com/rust-lang/crates.io-index"
+checksum = "5f0e2c6ed6606019b4e29e69dbaba95b11854410e5347d525002456dbbb786b6"
+
+[[package]]
+name = "cellular_raza"
+version = "0.2.4"
+source = "git+https://github.com/jonaspleyer/cellular_raza?tag=cellular_raza-v0.2.4#0123456789abcdef"
+"#;
+
+    #[test]
+    fn list_manifest_dependencies() -> crate::Result<()> {
+        let manifest = r#"
+[package]
+name = "example"
+
+[dependencies]
+serde = { workspace = true, features = ["derive"] }
+rand = "0.8"
+nalgebra_sparse = { package = "nalgebra-sparse", version = "0.10" }
+local = { path = "../local" }
+published = { path = "../published", version = "0.1" }
+forked = { git = "https://github.com/owner/forked" }
+internal = { version = "1", registry = "company" }
+
+[target.'cfg(unix)'.dependencies]
+libc = "0.2"
+
+[target.'cfg(windows)'.dev-dependencies]
+windows = "0.58"
+
+[dev-dependencies]
+tokio = "1"
+"#;
+        let workspace = "[workspace.dependencies]\nserde = { version = \"1.0\" }\n";
+        let dependencies = manifest_dependencies(manifest, Some(workspace), false)?;
+        let requests: Vec<_> = dependencies
+            .requests
+            .iter()
+            .map(|r| r.to_string())
+            .collect();
+        assert_eq!(
+            requests,
+            vec![
+                "nalgebra-sparse@0.10",
+                "published@0.1",
+                "rand@0.8",
+                "serde@1.0",
+                "libc@0.2"
+            ]
+        );
+        assert_eq!(dependencies.skipped, vec!["forked", "internal", "local"]);
+        let dependencies = manifest_dependencies(manifest, None, true)?;
+        assert_eq!(dependencies.requests.len(), 7);
+        Ok(())
+    }
+
+    #[test]
+    fn parse_lockfile_sources() -> crate::Result<()> {
+        let packages = parse_cargo_lock(CARGO_LOCK)?;
+        assert_eq!(packages.len(), 3);
+        assert_eq!(packages[0].source, PackageSource::Path);
+        assert!(packages[1].source.is_crates_io());
+        assert_eq!(
+            packages[2].source,
+            PackageSource::Git {
+                repository: "https://github.com/jonaspleyer/cellular_raza".to_string(),
+                reference: Some("cellular_raza-v0.2.4".to_string()),
+                commit: Some("0123456789abcdef".to_string()),
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn find_local_package_within_depth() -> crate::Result<()> {
+        let dir = std::env::temp_dir().join(format!("crate2bib-find-{}", std::process::id()));
+        let manifest = |name: &str| format!("[package]\nname = \"{name}\"\n");
+        for (path, name) in [
+            ("crates/shallow", "shallow"),
+            ("target/debug/built", "built"),
+            (".hidden/secret", "secret"),
+            ("a/b/c/d/e/deep", "deep"),
+        ] {
+            std::fs::create_dir_all(dir.join(path))?;
+            std::fs::write(dir.join(path).join("Cargo.toml"), manifest(name))?;
+        }
+        assert_eq!(
+            find_local_package(&dir, "shallow"),
+            Some(dir.join("crates/shallow"))
+        );
+        assert_eq!(find_local_package(&dir, "built"), None);
+        assert_eq!(find_local_package(&dir, "secret"), None);
+        assert_eq!(find_local_package(&dir, "deep"), None);
+        std::fs::remove_dir_all(&dir)?;
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn cite_local_path_dependency() -> crate::Result<()> {
+        let workspace_root = Path::new(env!("CARGO_MANIFEST_DIR")).parent().unwrap();
+        let packages = parse_cargo_lock(CARGO_LOCK)?;
+        let results =
+            get_biblatex_locked_package(&packages[0], workspace_root, None, vec!["CITATION.cff"])
+                .await?;
+        match &results[0] {
+            BibLaTeX::Repository(b) => {
+                assert_eq!(
+                    b.url.as_deref(),
+                    Some("https://github.com/jonaspleyer/crate2bib")
+                );
+                assert_eq!(b.version, Some(semver::Version::new(0, 5, 1)));
+                assert_eq!(b.license.as_deref(), Some("MIT OR Apache-2.0"));
+            }
+            _ => panic!("Got wrong return type"),
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn skip_broken_local_files() -> crate::Result<()> {
+        let dir = std::env::temp_dir().join(format!("crate2bib-broken-{}", std::process::id()));
+        std::fs::create_dir_all(&dir)?;
+        std::fs::write(
+            dir.join("CITATION.md"),
+            "```bibtex\n@software{broken, author = jane@example.org\n```\n",
+        )?;
+        std::fs::write(
+            dir.join("CITATION.bib"),
+            "@software{Doe2024, title = {rocket}}",
+        )?;
+        let results = local_search_files(
+            &reqwest::Client::new(),
+            &[dir.as_path()],
+            "https://github.com/owner/repo",
+            &["CITATION.md:bib", "CITATION.bib"],
+            false,
+        )
+        .await;
+        std::fs::remove_dir_all(&dir)?;
+        let results = results?;
+        assert_eq!(results.len(), 1);
+        assert!(matches!(
+            &results[0],
+            BibLaTeX::Plain(p) if p.bibliography.get("Doe2024").is_some()
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,467 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{BibLaTeX, BibLaTeXCratesIO, PlainBibLaTeX};
+
+/// Identifies where a [BibLaTeX] entry was obtained from
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize)]
+pub enum Source {
+    /// Information provided by [crates.io](https://crates.io)
+    CratesIO,
+    /// A `CITATION.cff` file inside the repository
+    CitationCff,
+    /// A `.zenodo.json` or `codemeta.json` file inside the repository
+    Metadata,
+    /// An entry resolved via [doi.org](https://doi.org)
+    Doi,
+    /// Metadata of the repository provided by its forge
+    Repository,
+    /// A bibliography file inside the repository which was not resolved via a DOI
+    Bibliography,
+    /// An archived release on [Zenodo](https://zenodo.org)
+    Zenodo,
+}
+
+impl std::fmt::Display for Source {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Source::CratesIO => f.write_str("crates.io"),
+            Source::CitationCff => f.write_str("CITATION.cff"),
+            Source::Metadata => f.write_str("metadata file"),
+            Source::Doi => f.write_str("doi.org"),
+            Source::Repository => f.write_str("repository"),
+            Source::Bibliography => f.write_str("bibliography file"),
+            Source::Zenodo => f.write_str("zenodo"),
+        }
+    }
+}
+
+/// The fields of a [BibLaTeXCratesIO] entry which are combined by [merge_biblatex]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize, Serialize)]
+pub enum Field {
+    /// See [BibLaTeXCratesIO::key]
+    Key,
+    /// See [BibLaTeXCratesIO::author]
+    Author,
+    /// See [BibLaTeXCratesIO::title]
+    Title,
+    /// See [BibLaTeXCratesIO::url]
+    Url,
+    /// See [BibLaTeXCratesIO::doi]
+    Doi,
+    /// See [BibLaTeXCratesIO::version]
+    Version,
+    /// See [BibLaTeXCratesIO::date]
+    Date,
+    /// See [BibLaTeXCratesIO::license]
+    License,
+    /// See [BibLaTeXCratesIO::keywords]
+    Keywords,
+    /// See [BibLaTeXCratesIO::swhid]
+    Swhid,
+}
+
+impl std::fmt::Display for Field {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Field::Key => f.write_str("key"),
+            Field::Author => f.write_str("author"),
+            Field::Title => f.write_str("title"),
+            Field::Url => f.write_str("url"),
+            Field::Doi => f.write_str("doi"),
+            Field::Version => f.write_str("version"),
+            Field::Date => f.write_str("date"),
+            Field::License => f.write_str("license"),
+            Field::Keywords => f.write_str("keywords"),
+            Field::Swhid => f.write_str("swhid"),
+        }
+    }
+}
+
+/// Determines which [Source] takes precedence for which [Field] when merging entries.
+///
+/// Sources which are not listed for a field are never used for it.
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct MergeConfig {
+    /// Ordered list of sources for every field. The first source which provides a value wins.
+    pub precedence: BTreeMap<Field, Vec<Source>>,
+}
+
+impl Default for MergeConfig {
+    /// Takes authors from `CITATION.cff`, version and date from crates.io, the DOI from doi.org
+    /// or Zenodo and the URL from the repository.
+    /// Bibliography files of the repository rank below the metadata of the repository and are
+    /// used last for the URL.
+    fn default() -> Self {
+        use Source::*;
+        let precedence = BTreeMap::from([
+            (
+                Field::Key,
+                vec![
+                    CitationCff,
+                    Metadata,
+                    CratesIO,
+                    Doi,
+                    Zenodo,
+                    Repository,
+                    Bibliography,
+                ],
+            ),
+            (
+                Field::Author,
+                vec![
+                    CitationCff,
+                    Metadata,
+                    Doi,
+                    Zenodo,
+                    CratesIO,
+                    Repository,
+                    Bibliography,
+                ],
+            ),
+            (
+                Field::Title,
+                vec![
+                    CratesIO,
+                    CitationCff,
+                    Metadata,
+                    Repository,
+                    Bibliography,
+                    Doi,
+                    Zenodo,
+                ],
+            ),
+            (
+                Field::Url,
+                vec![
+                    Repository,
+                    CitationCff,
+                    Metadata,
+                    CratesIO,
+                    Doi,
+                    Zenodo,
+                    Bibliography,
+                ],
+            ),
+            (
+                Field::Doi,
+                vec![
+                    Doi,
+                    Zenodo,
+                    CitationCff,
+                    Metadata,
+                    Repository,
+                    Bibliography,
+                    CratesIO,
+                ],
+            ),
+            (
+                Field::Version,
+                vec![
+                    CratesIO,
+                    CitationCff,
+                    Metadata,
+                    Repository,
+                    Bibliography,
+                    Zenodo,
+                    Doi,
+                ],
+            ),
+            (
+                Field::Date,
+                vec![
+                    CratesIO,
+                    CitationCff,
+                    Metadata,
+                    Repository,
+                    Bibliography,
+                    Zenodo,
+                    Doi,
+                ],
+            ),
+            (
+                Field::License,
+                vec![
+                    CratesIO,
+                    CitationCff,
+                    Metadata,
+                    Repository,
+                    Bibliography,
+                    Zenodo,
+                    Doi,
+                ],
+            ),
+            (
+                Field::Keywords,
+                vec![
+                    Metadata,
+                    CitationCff,
+                    CratesIO,
+                    Repository,
+                    Bibliography,
+                    Zenodo,
+                    Doi,
+                ],
+            ),
+            (
+                Field::Swhid,
+                vec![
+                    Repository,
+                    Bibliography,
+                    CratesIO,
+                    CitationCff,
+                    Metadata,
+                    Zenodo,
+                    Doi,
+                ],
+            ),
+        ]);
+        Self { precedence }
+    }
+}
+
+impl MergeConfig {
+    /// Overwrites the order of sources which is used for the given field
+    pub fn with_precedence(mut self, field: Field, sources: Vec<Source>) -> Self {
+        self.precedence.insert(field, sources);
+        self
+    }
+}
+
+/// A single entry which was combined from multiple [BibLaTeX] candidates
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct MergedBibLaTeX {
+    /// The combined entry
+    pub entry: BibLaTeXCratesIO,
+    /// Records from which source every field was taken
+    pub provenance: BTreeMap<Field, Source>,
+}
+
+impl std::fmt::Display for MergedBibLaTeX {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.entry.fmt(f)
+    }
+}
+
+impl BibLaTeX {
+    /// The [Source] from which this entry was obtained
+    pub fn source(&self) -> Source {
+        match self {
+            BibLaTeX::CratesIO(_) => Source::CratesIO,
+            BibLaTeX::CITATIONCFF(_) => Source::CitationCff,
+            BibLaTeX::SoftwareMetadata(_) => Source::Metadata,
+            BibLaTeX::Plain(PlainBibLaTeX { doi: Some(_), .. }) => Source::Doi,
+            BibLaTeX::Plain(_) => Source::Bibliography,
+            BibLaTeX::Repository(_) => Source::Repository,
+            BibLaTeX::Zenodo(_) => Source::Zenodo,
+        }
+    }
+
+    /// Converts the entry into the fields which can be merged.
+    fn merge_candidate(&self) -> Option<BibLaTeXCratesIO> {
+        match self {
+            BibLaTeX::CratesIO(b) | BibLaTeX::Repository(b) | BibLaTeX::Zenodo(b) => {
+                Some(b.clone())
+            }
+            BibLaTeX::CITATIONCFF(cff) => BibLaTeXCratesIO::from_citation_cff(cff).ok(),
+            BibLaTeX::SoftwareMetadata(m) => Some(m.to_biblatex()),
+            BibLaTeX::Plain(plain) => {
+                let entry = plain.selected_entries().into_iter().next()?;
+                let mut candidate = BibLaTeXCratesIO::from_entry(entry);
+                if plain.doi.is_none() {
+                    candidate.url = candidate.url.or(Some(plain.repository.clone()));
+                }
+                candidate.doi = candidate.doi.or(plain.doi.clone());
+                Some(candidate)
+            }
+        }
+    }
+}
+
+fn pick<T: Clone>(
+    candidates: &[(Source, BibLaTeXCratesIO)],
+    config: &MergeConfig,
+    field: Field,
+    provenance: &mut BTreeMap<Field, Source>,
+    get: impl Fn(&BibLaTeXCratesIO) -> Option<T>,
+) -> Option<T> {
+    for source in config.precedence.get(&field)?.iter() {
+        if let Some(value) = candidates
+            .iter()
+            .filter(|(s, _)| s == source)
+            .find_map(|(_, c)| get(c))
+        {
+            provenance.insert(field, *source);
+            return Some(value);
+        }
+    }
+    None
+}
+
+fn non_empty(s: &str) -> Option<String> {
+    if s.trim().is_empty() {
+        None
+    } else {
+        Some(s.to_string())
+    }
+}
+
+/// Combines all given entries field by field into one entry.
+///
+/// For every [Field], the sources listed in [MergeConfig::precedence] are tried in order and the
+/// first value found is used.
+/// Returns [None] if no entry could be converted.
+pub fn merge_biblatex(results: &[BibLaTeX], config: &MergeConfig) -> Option<MergedBibLaTeX> {
+    #[cfg(feature = "log")]
+    log::trace!("Merging {} entries", results.len());
+    let candidates: Vec<_> = results
+        .iter()
+        .filter_map(|r| r.merge_candidate().map(|c| (r.source(), c)))
+        .collect();
+    if candidates.is_empty() {
+        return None;
+    }
+
+    let crates_io = candidates
+        .iter()
+        .find(|(s, _)| *s == Source::CratesIO)
+        .map(|(_, c)| c);
+    let mut provenance = BTreeMap::new();
+    let p = &mut provenance;
+    let c = &candidates;
+    let entry = BibLaTeXCratesIO {
+        key: pick(c, config, Field::Key, p, |x| non_empty(&x.key)).unwrap_or_default(),
+        work_type: "software".to_string(),
+        author: pick(c, config, Field::Author, p, |x| {
+            (!x.author.is_empty()).then(|| x.author.clone())
+        })
+        .unwrap_or_default(),
+        title: pick(c, config, Field::Title, p, |x| non_empty(&x.title)).unwrap_or_default(),
+        url: pick(c, config, Field::Url, p, |x| x.url.clone()),
+        doi: pick(c, config, Field::Doi, p, |x| x.doi.clone()),
+        license: pick(c, config, Field::License, p, |x| x.license.clone()),
+        version: pick(c, config, Field::Version, p, |x| x.version.clone()),
+        date: pick(c, config, Field::Date, p, |x| x.date),
+        yanked: crates_io.is_some_and(|x| x.yanked),
+        notices: crates_io.map(|x| x.notices.clone()).unwrap_or_default(),
+        keywords: pick(c, config, Field::Keywords, p, |x| {
+            (!x.keywords.is_empty()).then(|| x.keywords.clone())
+        })
+        .unwrap_or_default(),
+        swhid: pick(c, config, Field::Swhid, p, |x| x.swhid.clone()),
+        note: None,
+        citation_files: crates_io
+            .map(|x| x.citation_files.clone())
+            .unwrap_or_default(),
+    };
+    Some(MergedBibLaTeX { entry, provenance })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn crates_io_entry() -> BibLaTeX {
+        BibLaTeX::CratesIO(BibLaTeXCratesIO {
+            key: "Pleyer2025".to_string(),
+            work_type: "software".to_string(),
+            author: vec![crate::person_from_name("jonaspleyer")],
+            title: "{cellular-raza}: Cellular Agent-based Modeling from a Clean Slate".to_string(),
+            url: Some("https://github.com/jonaspleyer/cellular_raza".to_string()),
+            doi: None,
+            license: Some("GPL-2.0".to_string()),
+            version: Some(semver::Version::new(0, 2, 4)),
+            date: chrono::NaiveDate::from_ymd_opt(2025, 6, 1)
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+                .map(|d| d.and_utc()),
+            ..Default::default()
+        })
+    }
+
+    fn doi_entry() -> BibLaTeX {
+        let bibliography = biblatex::Bibliography::parse(
+            "@article{Pleyer_2025,
+                author = {Pleyer, Jonas and Fleck, Christian},
+                doi = {10.21105/joss.07723},
+                title = {cellular\\_raza: Cellular Agent-based Modeling from a Clean Slate},
+                url = {http://dx.doi.org/10.21105/joss.07723},
+                year = {2025},
+            }",
+        )
+        .unwrap();
+        BibLaTeX::Plain(PlainBibLaTeX {
+            bibliography,
+            repository: "https://github.com/jonaspleyer/cellular_raza".to_string(),
+            filename: "CITATION.cff".to_string(),
+            doi: Some("10.21105/joss.07723".to_string()),
+            selected: vec![],
+            discovered: false,
+        })
+    }
+
+    fn repository_entry() -> BibLaTeX {
+        BibLaTeX::Repository(BibLaTeXCratesIO {
+            key: "jonaspleyer2025".to_string(),
+            work_type: "software".to_string(),
+            author: vec![crate::person_from_name("jonaspleyer")],
+            title: "cellular_raza".to_string(),
+            url: Some("https://github.com/jonaspleyer/cellular_raza/tree/v0.2.4".to_string()),
+            ..Default::default()
+        })
+    }
+
+    #[test]
+    fn merge_default_precedence() {
+        let merged = merge_biblatex(
+            &[doi_entry(), crates_io_entry(), repository_entry()],
+            &MergeConfig::default(),
+        )
+        .unwrap();
+        assert_eq!(merged.entry.author_names(), "Jonas Pleyer, Christian Fleck");
+        assert_eq!(merged.provenance[&Field::Author], Source::Doi);
+        assert_eq!(merged.entry.version, Some(semver::Version::new(0, 2, 4)));
+        assert_eq!(merged.provenance[&Field::Version], Source::CratesIO);
+        assert_eq!(merged.entry.doi.as_deref(), Some("10.21105/joss.07723"));
+        assert_eq!(merged.provenance[&Field::Doi], Source::Doi);
+        assert_eq!(
+            merged.entry.url.as_deref(),
+            Some("https://github.com/jonaspleyer/cellular_raza/tree/v0.2.4")
+        );
+        assert_eq!(merged.provenance[&Field::Url], Source::Repository);
+        assert_eq!(merged.provenance[&Field::Title], Source::CratesIO);
+    }
+
+    #[test]
+    fn rank_bibliography_files_below_repository() {
+        let mut bibliography = doi_entry();
+        if let BibLaTeX::Plain(plain) = &mut bibliography {
+            plain.doi = None;
+            plain.filename = "citation.bib".to_string();
+        }
+        assert_eq!(bibliography.source(), Source::Bibliography);
+        let merged = merge_biblatex(
+            &[bibliography.clone(), crates_io_entry()],
+            &MergeConfig::default(),
+        )
+        .unwrap();
+        assert_eq!(
+            merged.entry.url.as_deref(),
+            Some("https://github.com/jonaspleyer/cellular_raza")
+        );
+        assert_eq!(merged.provenance[&Field::Url], Source::CratesIO);
+        let merged =
+            merge_biblatex(&[bibliography, repository_entry()], &MergeConfig::default()).unwrap();
+        assert_eq!(merged.provenance[&Field::Url], Source::Repository);
+        assert_eq!(merged.provenance[&Field::Doi], Source::Bibliography);
+    }
+
+    #[test]
+    fn merge_custom_precedence() {
+        let config = MergeConfig::default().with_precedence(Field::Author, vec![Source::CratesIO]);
+        let merged = merge_biblatex(&[doi_entry(), crates_io_entry()], &config).unwrap();
+        assert_eq!(merged.entry.author_names(), "jonaspleyer");
+        assert_eq!(merged.provenance[&Field::Author], Source::CratesIO);
+        assert!(merge_biblatex(&[], &config).is_none());
+    }
+}
@@ -0,0 +1,492 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::BibLaTeXCratesIO;
+
+/// Format of a [SoftwareMetadata] file
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum MetadataFormat {
+    /// A `.zenodo.json` file which is used by the github integration of
+    /// [Zenodo](https://zenodo.org)
+    ZenodoJson,
+    /// A [codemeta.json](https://codemeta.github.io) file
+    CodeMeta,
+}
+
+impl std::fmt::Display for MetadataFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MetadataFormat::ZenodoJson => f.write_str(".zenodo.json"),
+            MetadataFormat::CodeMeta => f.write_str("codemeta.json"),
+        }
+    }
+}
+
+/// A person or organization which created the software
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Creator {
+    /// Family name of a person or name of an organization
+    pub family_name: String,
+    /// Given names of a person
+    pub given_name: Option<String>,
+    /// The [ORCID](https://orcid.org) such as `0000-0002-1825-0097`
+    pub orcid: Option<String>,
+    /// Affiliation of the creator
+    pub affiliation: Option<String>,
+}
+
+impl std::fmt::Display for Creator {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if let Some(given_name) = &self.given_name {
+            write!(f, "{given_name} ")?;
+        }
+        f.write_str(&self.family_name)
+    }
+}
+
+/// An identifier of a work which is related to the software such as the accompanying paper
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct RelatedIdentifier {
+    /// The identifier itself such as a DOI or url
+    pub identifier: String,
+    /// The relation to the software such as `isSupplementTo`
+    pub relation: Option<String>,
+}
+
+/// Citation metadata of software obtained from a `.zenodo.json` or `codemeta.json` file
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct SoftwareMetadata {
+    /// The format of the file from which the metadata was parsed
+    pub format: Option<MetadataFormat>,
+    /// Name of the software
+    pub title: String,
+    /// Short description
+    pub description: Option<String>,
+    /// All authors of the software
+    pub creators: Vec<Creator>,
+    /// Keywords describing the software
+    pub keywords: Vec<String>,
+    /// Identifier of the license
+    pub license: Option<String>,
+    /// Version of the software
+    pub version: Option<String>,
+    /// Date of publication in the format `YYYY-MM-DD`
+    pub date: Option<String>,
+    /// The [DOI](https://www.doi.org/) of the software
+    pub doi: Option<String>,
+    /// Link to the repository
+    pub url: Option<String>,
+    /// Identifiers of related works
+    pub related_identifiers: Vec<RelatedIdentifier>,
+}
+
+fn json_text(value: Option<&Value>) -> Option<String> {
+    match value? {
+        Value::String(s) => Some(s.trim().to_string()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Array(a) => json_text(a.first()),
+        // codemeta allows objects such as {"@type": "Organization", "name": "..."}
+        Value::Object(o) => json_text(o.get("name").or(o.get("@id"))),
+        _ => None,
+    }
+    .filter(|s| !s.is_empty())
+}
+
+fn list(value: Option<&Value>) -> Vec<&Value> {
+    match value {
+        Some(Value::Array(a)) => a.iter().collect(),
+        Some(v) => vec![v],
+        None => vec![],
+    }
+}
+
+fn keywords(value: Option<&Value>) -> Vec<String> {
+    list(value)
+        .into_iter()
+        .filter_map(|k| k.as_str())
+        .flat_map(|k| k.split(','))
+        .map(|k| k.trim().to_string())
+        .filter(|k| !k.is_empty())
+        .collect()
+}
+
+fn strip_orcid(orcid: &str) -> String {
+    orcid
+        .trim()
+        .trim_start_matches("https://orcid.org/")
+        .trim_start_matches("http://orcid.org/")
+        .to_string()
+}
+
+fn strip_license(license: &str) -> String {
+    license
+        .trim()
+        .trim_start_matches("https://spdx.org/licenses/")
+        .trim_start_matches("http://spdx.org/licenses/")
+        .trim_end_matches(".html")
+        .to_string()
+}
+
+fn doi_of(identifier: &str) -> Option<String> {
+    let doi = crate::normalize_doi(identifier);
+    doi.starts_with("10.").then(|| doi.to_string())
+}
+
+/// Finds the link to the repository such as `https://github.com/owner/repo/tree/v0.1.0` among
+/// the related identifiers and strips the revision
+fn repository_of(related_identifiers: &[RelatedIdentifier]) -> Option<String> {
+    related_identifiers
+        .iter()
+        .map(|r| r.identifier.as_str())
+        .filter(|i| i.starts_with("http") && doi_of(i).is_none())
+        .map(|i| i.split("/tree/").next().unwrap_or(i).to_string())
+        .next()
+}
+
+/// Describes a relation such as `isSupplementTo` as `Supplement to`
+fn describe_relation(relation: &str) -> String {
+    let relation = relation.strip_prefix("is").unwrap_or(relation);
+    let mut out = String::new();
+    for c in relation.chars() {
+        if out.is_empty() {
+            out.extend(c.to_uppercase());
+        } else if c.is_uppercase() {
+            out.push(' ');
+            out.extend(c.to_lowercase());
+        } else {
+            out.push(c);
+        }
+    }
+    out
+}
+
+impl SoftwareMetadata {
+    /// Parses a `.zenodo.json` file
+    ///
+    /// The link to the repository is taken from the related identifiers.
+    pub fn from_zenodo_json(text: &str) -> crate::Result<Self> {
+        let json: Value = serde_json::from_str(text)?;
+        let related_identifiers: Vec<_> = list(json.get("related_identifiers"))
+            .into_iter()
+            .filter_map(|r| {
+                Some(RelatedIdentifier {
+                    identifier: json_text(r.get("identifier"))?,
+                    relation: json_text(r.get("relation")),
+                })
+            })
+            .collect();
+        let creators = list(json.get("creators"))
+            .into_iter()
+            .map(|c| {
+                let name = json_text(c.get("name")).unwrap_or_default();
+                let (family_name, given_name) = match name.split_once(',') {
+                    Some((family, given)) => {
+                        (family.trim().to_string(), Some(given.trim().to_string()))
+                    }
+                    None => (name, None),
+                };
+                Creator {
+                    family_name,
+                    given_name,
+                    orcid: json_text(c.get("orcid")).map(|o| strip_orcid(&o)),
+                    affiliation: json_text(c.get("affiliation")),
+                }
+            })
+            .collect();
+        Ok(Self {
+            format: Some(MetadataFormat::ZenodoJson),
+            title: json_text(json.get("title")).unwrap_or_default(),
+            description: json_text(json.get("description")),
+            creators,
+            keywords: keywords(json.get("keywords")),
+            license: json_text(json.get("license").map(|l| l.get("id").unwrap_or(l))),
+            version: json_text(json.get("version")),
+            date: json_text(json.get("publication_date")),
+            doi: json_text(json.get("doi")),
+            url: repository_of(&related_identifiers),
+            related_identifiers,
+        })
+    }
+
+    /// Parses a [codemeta.json](https://codemeta.github.io) file
+    pub fn from_codemeta(text: &str) -> crate::Result<Self> {
+        let json: Value = serde_json::from_str(text)?;
+        let creators = list(json.get("author"))
+            .into_iter()
+            .map(|a| {
+                let family_name = json_text(a.get("familyName"))
+                    .or(json_text(a.get("name")))
+                    .unwrap_or_default();
+                Creator {
+                    family_name,
+                    given_name: json_text(a.get("givenName")),
+                    orcid: json_text(a.get("@id"))
+                        .or(json_text(a.get("identifier")))
+                        .filter(|id| id.contains("orcid.org"))
+                        .map(|o| strip_orcid(&o)),
+                    affiliation: json_text(a.get("affiliation")),
+                }
+            })
+            .collect();
+        let identifiers: Vec<_> = list(json.get("identifier"))
+            .into_iter()
+            .filter_map(|i| json_text(i.get("value")).or(json_text(Some(i))))
+            .collect();
+        let mut related_identifiers: Vec<_> = list(json.get("relatedLink"))
+            .into_iter()
+            .filter_map(|r| json_text(Some(r)))
+            .map(|identifier| RelatedIdentifier {
+                identifier,
+                relation: None,
+            })
+            .collect();
+        related_identifiers.extend(
+            list(json.get("referencePublication"))
+                .into_iter()
+                .filter_map(|r| json_text(r.get("@id")).or(json_text(r.get("identifier"))))
+                .map(|identifier| RelatedIdentifier {
+                    identifier,
+                    relation: Some("isSupplementTo".to_string()),
+                }),
+        );
+        Ok(Self {
+            format: Some(MetadataFormat::CodeMeta),
+            title: json_text(json.get("name")).unwrap_or_default(),
+            description: json_text(json.get("description")),
+            creators,
+            keywords: keywords(json.get("keywords")),
+            license: json_text(json.get("license")).map(|l| strip_license(&l)),
+            version: json_text(json.get("version")).or(json_text(json.get("softwareVersion"))),
+            date: json_text(json.get("datePublished")).or(json_text(json.get("dateModified"))),
+            doi: identifiers.iter().find_map(|i| doi_of(i)),
+            url: json_text(json.get("codeRepository")).or(json_text(json.get("url"))),
+            related_identifiers,
+        })
+    }
+
+    /// Describes all related works except for the repository as in
+    /// `Supplement to https://doi.org/10.21105/joss.07723`
+    fn related_note(&self) -> Option<String> {
+        let url = self.url.as_deref().map(crate::normalize_url);
+        let related: Vec<_> = self
+            .related_identifiers
+            .iter()
+            .filter(|r| {
+                let identifier = crate::normalize_url(&r.identifier);
+                url.as_ref().is_none_or(|url| {
+                    identifier != *url && !identifier.starts_with(&format!("{url}/tree/"))
+                })
+            })
+            .map(|r| {
+                let identifier = match doi_of(&r.identifier) {
+                    Some(doi) => format!("https://doi.org/{doi}"),
+                    None => r.identifier.clone(),
+                };
+                match &r.relation {
+                    Some(relation) => format!("{} {identifier}", describe_relation(relation)),
+                    None => format!("Related to {identifier}"),
+                }
+            })
+            .collect();
+        (!related.is_empty()).then(|| related.join("; "))
+    }
+
+    /// Converts the metadata into a `@software` entry.
+    ///
+    /// Related works are described in the note of the entry.
+    /// See [SoftwareMetadata::to_entry] to also keep the ORCIDs and affiliations of the creators.
+    pub fn to_biblatex(&self) -> BibLaTeXCratesIO {
+        let date = self
+            .date
+            .as_ref()
+            .and_then(|d| chrono::NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+            .and_then(|d| d.and_hms_opt(0, 0, 0))
+            .map(|d| d.and_utc());
+        BibLaTeXCratesIO {
+            key: format!(
+                "{}{}",
+                self.creators
+                    .first()
+                    .map_or(self.title.clone(), |c| c.family_name.clone()),
+                self.date
+                    .as_ref()
+                    .and_then(|d| d.get(..4))
+                    .unwrap_or_default()
+            ),
+            work_type: "software".to_string(),
+            author: self
+                .creators
+                .iter()
+                .map(|c| match &c.given_name {
+                    Some(given_name) => biblatex::Person {
+                        given_name: given_name.clone(),
+                        ..crate::person_from_name(&c.family_name)
+                    },
+                    None => crate::organization(&c.family_name),
+                })
+                .collect(),
+            title: self
+                .description
+                .as_ref()
+                .map_or(format!("{{{}}}", self.title), |d| {
+                    format!("{{{}}}: {d}", self.title)
+                }),
+            url: self.url.clone(),
+            doi: self.doi.clone(),
+            license: self.license.clone(),
+            version: self
+                .version
+                .as_ref()
+                .and_then(|v| semver::Version::parse(v.trim_start_matches('v')).ok()),
+            date,
+            keywords: self.keywords.clone(),
+            note: self.related_note(),
+            ..Default::default()
+        }
+    }
+
+    /// Converts the metadata into a [biblatex::Entry] like [SoftwareMetadata::to_biblatex].
+    ///
+    /// The ORCIDs and affiliations of the creators are written as
+    /// [data annotations](https://ctan.org/pkg/biblatex) of the authors as in
+    /// `author+an:orcid = {1="0009-0001-0613-7978"}`.
+    pub fn to_entry(&self) -> biblatex::Entry {
+        let mut entry = self.to_biblatex().to_entry();
+        let annotate = |value: fn(&Creator) -> Option<&String>| {
+            let annotations: Vec<_> = self
+                .creators
+                .iter()
+                .enumerate()
+                .filter_map(|(n, c)| Some(format!("{}=\"{}\"", n + 1, value(c)?)))
+                .collect();
+            (!annotations.is_empty()).then(|| {
+                vec![biblatex::Spanned::detached(biblatex::Chunk::Normal(
+                    annotations.join("; "),
+                ))]
+            })
+        };
+        if let Some(orcids) = annotate(|c| c.orcid.as_ref()) {
+            entry.set("author+an:orcid", orcids);
+        }
+        if let Some(affiliations) = annotate(|c| c.affiliation.as_ref()) {
+            entry.set("author+an:affiliation", affiliations);
+        }
+        entry
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_zenodo_json() -> crate::Result<()> {
+        let metadata = SoftwareMetadata::from_zenodo_json(
+            r#"{
+                "title": "cellular_raza",
+                "upload_type": "software",
+                "creators": [
+                    {
+                        "name": "Pleyer, Jonas",
+                        "orcid": "0009-0001-0613-7978",
+                        "affiliation": "University of Freiburg"
+                    },
+                    {"name": "The cellular_raza developers"}
+                ],
+                "keywords": ["agent-based", "simulation"],
+                "license": {"id": "GPL-2.0"},
+                "version": "0.2.4",
+                "publication_date": "2025-06-02",
+                "related_identifiers": [
+                    {
+                        "identifier": "10.21105/joss.07723",
+                        "relation": "isSupplementTo",
+                        "scheme": "doi"
+                    },
+                    {
+                        "identifier": "https://github.com/jonaspleyer/cellular_raza/tree/cellular_raza-v0.2.4",
+                        "relation": "isSupplementTo",
+                        "scheme": "url"
+                    }
+                ]
+            }"#,
+        )?;
+        assert_eq!(
+            metadata.creators[0],
+            Creator {
+                family_name: "Pleyer".to_string(),
+                given_name: Some("Jonas".to_string()),
+                orcid: Some("0009-0001-0613-7978".to_string()),
+                affiliation: Some("University of Freiburg".to_string()),
+            }
+        );
+        assert_eq!(metadata.related_identifiers.len(), 2);
+        assert_eq!(
+            metadata.url.as_deref(),
+            Some("https://github.com/jonaspleyer/cellular_raza")
+        );
+        let expected = "\
+@software{Pleyer2025,
+    author = {Jonas Pleyer and {The cellular\\_raza developers}},
+    title = {{cellular\\_raza}},
+    url = {https://github.com/jonaspleyer/cellular_raza},
+    date = {2025-06-02},
+    version = {0.2.4},
+    license = {GPL-2.0},
+    keywords = {agent-based, simulation},
+    note = {Supplement to https://doi.org/10.21105/joss.07723},
+}";
+        assert_eq!(format!("{}", metadata.to_biblatex()), expected);
+        let entry = crate::entry_to_biblatex_string(&metadata.to_entry(), crate::Encoding::Utf8);
+        assert!(entry.contains("author+an:orcid = {1=\"0009-0001-0613-7978\"},"));
+        assert!(entry.contains("author+an:affiliation = {1=\"University of Freiburg\"},"));
+        Ok(())
+    }
+
+    #[test]
+    fn parse_codemeta() -> crate::Result<()> {
+        let metadata = SoftwareMetadata::from_codemeta(
+            r#"{
+                "@context": "https://w3id.org/codemeta/3.0",
+                "@type": "SoftwareSourceCode",
+                "name": "crate2bib",
+                "description": "Create BibLaTeX entries for crates hosted on crates.io",
+                "codeRepository": "https://github.com/jonaspleyer/crate2bib",
+                "license": "https://spdx.org/licenses/MIT",
+                "version": "0.5.1",
+                "datePublished": "2025-01-15",
+                "keywords": "citation, biblatex",
+                "identifier": {"@type": "PropertyValue", "propertyID": "DOI", "value": "https://doi.org/10.5281/zenodo.1"},
+                "author": [
+                    {
+                        "@type": "Person",
+                        "@id": "https://orcid.org/0009-0001-0613-7978",
+                        "givenName": "Jonas",
+                        "familyName": "Pleyer",
+                        "affiliation": {"@type": "Organization", "name": "University of Freiburg"}
+                    }
+                ],
+                "referencePublication": {"@id": "https://doi.org/10.21105/joss.07723"}
+            }"#,
+        )?;
+        assert_eq!(
+            metadata.creators[0].orcid.as_deref(),
+            Some("0009-0001-0613-7978")
+        );
+        assert_eq!(
+            metadata.creators[0].affiliation.as_deref(),
+            Some("University of Freiburg")
+        );
+        assert_eq!(metadata.keywords, vec!["citation", "biblatex"]);
+        assert_eq!(metadata.license.as_deref(), Some("MIT"));
+        assert_eq!(metadata.doi.as_deref(), Some("10.5281/zenodo.1"));
+        let entry = metadata.to_biblatex();
+        assert_eq!(entry.key, "Pleyer2025");
+        assert_eq!(entry.version, Some(semver::Version::new(0, 5, 1)));
+        assert_eq!(
+            entry.url.as_deref(),
+            Some("https://github.com/jonaspleyer/crate2bib")
+        );
+        Ok(())
+    }
+}
@@ -2,12 +2,32 @@ use crate::*;
 
 use pyo3::prelude::*;
 
+/// Formats every result in the given format which is either `biblatex`, `hayagriva` or a
+/// [CitationStyle] such as `apa` for plain-text references
+fn format_results(results: &[BibLaTeX], format: &str) -> PyResult<Vec<String>> {
+    results
+        .iter()
+        .map(|x| match format {
+            "biblatex" => Ok(format!("{x}")),
+            "hayagriva" => Ok(x.to_hayagriva()?),
+            f if f.parse::<CitationStyle>().is_ok() => {
+                Ok(x.format_references(f.parse()?, Markup::Text)?)
+            }
+            f => Err(pyo3::exceptions::PyValueError::new_err(format!(
+                "unknown format {f}"
+            ))),
+        })
+        .collect()
+}
+
 /// Wraps the [crate2bib::get_biblatex] function.
 ///
 /// Args:
 ///     crate_name(str): Name of the crate to get BibLaTeX entry
 ///     version (str): A semver-compliant version number for the crate
 ///     user_agent (:obj:`str`, optional):: The name of the user agent. Defaults to None.
+///     format (:obj:`str`, optional): "biblatex", "hayagriva" or a citation style
+///         such as "apa" or "ieee". Defaults to "biblatex".
 /// Returns:
 ///     tuple: The formatted BibLaTeX entry and its origin given by [crate2bib::EntryOrigin]
 #[pyfunction]
@@ -22,6 +42,7 @@ use pyo3::prelude::*;
             "CITATION.cff".to_string(),
             "citation.bib".to_string()
         ],
+        format = "biblatex".to_string(),
     ),
 )]
 fn get_biblatex_py(
@@ -31,6 +52,7 @@ fn get_biblatex_py(
     user_agent: Option<String>,
     branch_name: Option<String>,
     filenames: Vec<String>,
+    format: String,
 ) -> PyResult<Bound<PyAny>> {
     pyo3_async_runtimes::tokio::future_into_py(py, async move {
         let filenames = filenames.iter().map(|x| x.as_str()).collect();
@@ -42,10 +64,52 @@ fn get_biblatex_py(
             filenames,
         )
         .await?;
-        Ok(results
-            .into_iter()
-            .map(|x| format!("{x}"))
-            .collect::<Vec<_>>())
+        format_results(&results, &format)
+    })
+}
+
+/// Wraps the [crate2bib::get_biblatex_repository] function.
+///
+/// Args:
+///     repository(str): Link to the repository
+///     rev (:obj:`str`, optional): A branch, tag or commit. Defaults to the default branch.
+///     user_agent (:obj:`str`, optional):: The name of the user agent. Defaults to None.
+///     format (:obj:`str`, optional): "biblatex", "hayagriva" or a citation style
+///         such as "apa" or "ieee". Defaults to "biblatex".
+/// Returns:
+///     list: The formatted BibLaTeX entries
+#[pyfunction]
+#[pyo3(
+    name = "get_biblatex_repository",
+    signature = (
+        repository,
+        rev = None,
+        user_agent = None,
+        filenames = vec![
+            "CITATION.cff".to_string(),
+            "citation.bib".to_string()
+        ],
+        format = "biblatex".to_string(),
+    ),
+)]
+fn get_biblatex_repository_py(
+    py: Python,
+    repository: String,
+    rev: Option<String>,
+    user_agent: Option<String>,
+    filenames: Vec<String>,
+    format: String,
+) -> PyResult<Bound<PyAny>> {
+    pyo3_async_runtimes::tokio::future_into_py(py, async move {
+        let filenames = filenames.iter().map(|x| x.as_str()).collect();
+        let results = get_biblatex_repository(
+            &repository,
+            rev.as_deref(),
+            user_agent.as_deref(),
+            filenames,
+        )
+        .await?;
+        format_results(&results, &format)
     })
 }
 
@@ -53,6 +117,7 @@ fn get_biblatex_py(
 #[pymodule]
 fn crate2bib(m: &Bound<'_, PyModule>) -> PyResult<()> {
     m.add_function(wrap_pyfunction!(get_biblatex_py, m)?)?;
+    m.add_function(wrap_pyfunction!(get_biblatex_repository_py, m)?)?;
     m.add_class::<BibLaTeXCratesIO>()?;
     Ok(())
 }
@@ -0,0 +1,433 @@
+use std::path::Path;
+
+use chrono::Datelike;
+use serde::{Deserialize, Serialize};
+
+use crate::BibLaTeXCratesIO;
+
+/// A cargo registry which is accessed via the
+/// [sparse index protocol](https://doc.rust-lang.org/cargo/reference/registry-index.html#sparse-protocol)
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Registry {
+    /// Name of the registry as specified in `.cargo/config.toml`
+    pub name: Option<String>,
+    /// Base url of the sparse index without the `sparse+` prefix
+    pub index: String,
+    /// Token which is sent in the `Authorization` header to registries which require it
+    pub token: Option<String>,
+}
+
+/// A single line of a file in the sparse index
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct IndexVersion {
+    /// Name of the crate
+    pub name: String,
+    /// Version of the crate
+    pub vers: String,
+    /// Indicates if this version was yanked
+    #[serde(default)]
+    pub yanked: bool,
+}
+
+/// Contents of the `config.json` file at the root of the index
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RegistryConfig {
+    /// Download location of crates
+    pub dl: String,
+    /// Base url of the web API of the registry
+    pub api: Option<String>,
+}
+
+impl Registry {
+    /// The default [crates.io](https://crates.io) registry
+    pub fn crates_io() -> Self {
+        Self::from_url("sparse+https://index.crates.io/")
+    }
+
+    /// Creates a registry from the url of its sparse index.
+    ///
+    /// The `sparse+` prefix is optional.
+    pub fn from_url(url: &str) -> Self {
+        Self {
+            name: None,
+            index: url
+                .strip_prefix("sparse+")
+                .unwrap_or(url)
+                .trim_end_matches('/')
+                .to_string(),
+            token: None,
+        }
+    }
+
+    /// Looks up the registry with the given name in the cargo configuration.
+    ///
+    /// The index url is taken from the `CARGO_REGISTRIES_<NAME>_INDEX` environment variable or the
+    /// `[registries.<name>]` table of `.cargo/config.toml` files in `directory`, its parents and
+    /// `$CARGO_HOME`.
+    /// A token is read from the `CARGO_REGISTRIES_<NAME>_TOKEN` environment variable or the
+    /// `[registries.<name>]` table of `$CARGO_HOME/credentials.toml`.
+    pub fn from_cargo_config(name: &str, directory: &Path) -> crate::Result<Self> {
+        if name == "crates-io" {
+            return Ok(Self::crates_io());
+        }
+        let env_name = name.to_uppercase().replace('-', "_");
+        let index = match std::env::var(format!("CARGO_REGISTRIES_{env_name}_INDEX")) {
+            Ok(index) => Some(index),
+            Err(_) => cargo_config_files(directory)
+                .into_iter()
+                .filter_map(|file| std::fs::read_to_string(file).ok())
+                .filter_map(|contents| contents.parse::<toml::Table>().ok())
+                .find_map(|config| {
+                    config
+                        .get("registries")?
+                        .get(name)?
+                        .get("index")?
+                        .as_str()
+                        .map(|x| x.to_string())
+                }),
+        }
+        .ok_or(crate::NotFoundError(format!(
+            "Could not find registry {name} in cargo configuration"
+        )))?;
+        if !index.starts_with("sparse+") {
+            return Err(crate::NotFoundError(format!(
+                "Registry {name} does not use the sparse protocol"
+            ))
+            .into());
+        }
+        let token = std::env::var(format!("CARGO_REGISTRIES_{env_name}_TOKEN"))
+            .ok()
+            .or_else(|| {
+                let cargo_home = cargo_home()?;
+                let credentials: Vec<_> = ["credentials.toml", "credentials"]
+                    .into_iter()
+                    .filter_map(|file| std::fs::read_to_string(cargo_home.join(file)).ok())
+                    .collect();
+                credentials_token(name, &credentials)
+            });
+        Ok(Self {
+            name: Some(name.to_string()),
+            token,
+            ..Self::from_url(&index)
+        })
+    }
+
+    fn request(&self, client: &reqwest::Client, url: &str) -> reqwest::RequestBuilder {
+        let request = client.get(url);
+        match &self.token {
+            Some(token) => request.header(reqwest::header::AUTHORIZATION, token),
+            None => request,
+        }
+    }
+
+    /// Obtains all published versions of a crate from the index
+    pub async fn index_versions(
+        &self,
+        client: &reqwest::Client,
+        crate_name: &str,
+    ) -> crate::Result<Vec<IndexVersion>> {
+        let url = format!("{}/{}", self.index, index_path(crate_name));
+        #[cfg(feature = "log")]
+        log::trace!("Requesting index file \"{url}\"");
+        let response = self.request(client, &url).send().await?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(crate::NotFoundError(format!("Could not find crate {crate_name}")).into());
+        }
+        let text = response.error_for_status()?.text().await?;
+        Ok(text
+            .lines()
+            .filter(|l| !l.trim().is_empty())
+            .map(serde_json::from_str)
+            .collect::<Result<_, _>>()?)
+    }
+
+    /// Obtains the `config.json` file of the index
+    pub async fn config(&self, client: &reqwest::Client) -> crate::Result<RegistryConfig> {
+        Ok(self
+            .request(client, &format!("{}/config.json", self.index))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await?)
+    }
+}
+
+/// All `.cargo/config.toml` files which apply to the given directory ordered by precedence
+fn cargo_config_files(directory: &Path) -> Vec<std::path::PathBuf> {
+    let mut files = vec![];
+    for dir in directory.ancestors() {
+        files.push(dir.join(".cargo").join("config.toml"));
+        files.push(dir.join(".cargo").join("config"));
+    }
+    if let Some(cargo_home) = cargo_home() {
+        files.push(cargo_home.join("config.toml"));
+        files.push(cargo_home.join("config"));
+    }
+    files
+}
+
+fn cargo_home() -> Option<std::path::PathBuf> {
+    std::env::var("CARGO_HOME")
+        .ok()
+        .map(std::path::PathBuf::from)
+        .or(std::env::var("HOME")
+            .ok()
+            .map(|h| Path::new(&h).join(".cargo")))
+}
+
+/// Finds the token of a registry in the contents of `credentials.toml` files
+fn credentials_token(name: &str, credentials: &[String]) -> Option<String> {
+    credentials
+        .iter()
+        .filter_map(|contents| contents.parse::<toml::Table>().ok())
+        .find_map(|credentials| {
+            credentials
+                .get("registries")?
+                .get(name)?
+                .get("token")?
+                .as_str()
+                .map(|x| x.to_string())
+        })
+}
+
+/// Path of the file inside the index which lists all versions of a crate
+pub fn index_path(crate_name: &str) -> String {
+    let name = crate_name.to_lowercase();
+    match name.len() {
+        1 => format!("1/{name}"),
+        2 => format!("2/{name}"),
+        3 => format!("3/{}/{name}", &name[..1]),
+        _ => format!("{}/{}/{name}", &name[..2], &name[2..4]),
+    }
+}
+
+/// Returns a [BibLaTeXCratesIO] entry for a crate hosted on the given [Registry].
+///
+/// The version is resolved from the sparse index in the same way as in
+/// [crate::generate_biblatex_crates_io].
+/// Further information such as authors, description and publication date are obtained from the
+/// web API of the registry if it provides one.
+pub async fn generate_biblatex_registry(
+    crate_name: &str,
+    version: Option<&str>,
+    options: &crate::ResolveOptions,
+    registry: &Registry,
+    client: &reqwest::Client,
+) -> crate::Result<BibLaTeXCratesIO> {
+    #[cfg(feature = "log")]
+    log::trace!("Obtaining versions from index");
+    let versions = registry.index_versions(client, crate_name).await?;
+    let candidates: Vec<_> = versions
+        .iter()
+        .map(|x| crate::VersionCandidate {
+            num: x.vers.clone(),
+            yanked: x.yanked,
+        })
+        .collect();
+    let resolution = crate::resolve_version(crate_name, &candidates, version, options)?;
+    let found_version = &versions[resolution.index];
+    let found_version_semver = resolution.version;
+
+    #[cfg(feature = "log")]
+    log::trace!("Obtaining information from web API");
+    let api = registry.config(client).await.ok().and_then(|c| c.api);
+    let info = match api {
+        Some(api) => match registry
+            .request(
+                client,
+                &format!("{}/api/v1/crates/{crate_name}", api.trim_end_matches('/')),
+            )
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+        {
+            Ok(response) => response.json::<serde_json::Value>().await.ok(),
+            Err(_e) => {
+                #[cfg(feature = "log")]
+                log::warn!("Could not obtain information from web API: {_e}");
+                None
+            }
+        },
+        None => None,
+    };
+
+    let crate_data = info.as_ref().and_then(|i| i.get("crate"));
+    let version_data = info
+        .as_ref()
+        .and_then(|i| i.get("versions"))
+        .and_then(|v| v.as_array())
+        .and_then(|v| {
+            v.iter()
+                .find(|x| x.get("num").and_then(|n| n.as_str()) == Some(&found_version.vers))
+        });
+    let get_str = |value: Option<&serde_json::Value>, key: &str| {
+        value
+            .and_then(|v| v.get(key))
+            .and_then(|x| x.as_str())
+            .map(|x| x.to_string())
+    };
+    let published_by = version_data.and_then(|v| v.get("published_by"));
+    let author_name = get_str(published_by, "name");
+    let date = get_str(version_data, "created_at")
+        .and_then(|d| d.parse::<chrono::DateTime<chrono::Utc>>().ok());
+    let updated_at = get_str(crate_data, "updated_at")
+        .and_then(|d| d.parse::<chrono::DateTime<chrono::Utc>>().ok());
+
+    #[cfg(feature = "log")]
+    log::trace!("Bundling Information into BibLaTeXCratesIO");
+    Ok(BibLaTeXCratesIO {
+        key: format!(
+            "{}{}",
+            author_name
+                .as_ref()
+                .and_then(|x| x.split(" ").nth(1).map(|x| x.to_string()))
+                .unwrap_or(crate_name.to_string()),
+            updated_at
+                .or(date)
+                .map(|d| d.year().to_string())
+                .unwrap_or_default()
+        ),
+        work_type: "software".to_string(),
+        author: author_name
+            .or(get_str(published_by, "login"))
+            .map(|x| crate::person_from_name(&x))
+            .into_iter()
+            .collect(),
+        title: get_str(crate_data, "description").map_or(format!("{{{}}}", crate_name), |x| {
+            format!("{{{}}}: {}", crate_name, x)
+        }),
+        url: get_str(crate_data, "repository"),
+        doi: None,
+        license: get_str(version_data, "license"),
+        version: Some(found_version_semver),
+        date,
+        yanked: found_version.yanked,
+        ..Default::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sparse_index_paths() {
+        assert_eq!(index_path("a"), "1/a");
+        assert_eq!(index_path("ab"), "2/ab");
+        assert_eq!(index_path("abc"), "3/a/abc");
+        assert_eq!(index_path("Serde"), "se/rd/serde");
+    }
+
+    #[tokio::test]
+    async fn query_sparse_registry() -> crate::Result<()> {
+        let mut server = mockito::Server::new_async().await;
+        let index = server
+            .mock("GET", "/my/-c/my-crate")
+            .with_body(
+                "{\"name\":\"my-crate\",\"vers\":\"0.1.0\",\"deps\":[],\"yanked\":false}\n\
+                {\"name\":\"my-crate\",\"vers\":\"0.2.0\",\"deps\":[],\"yanked\":false}\n",
+            )
+            .create_async()
+            .await;
+        let config = server
+            .mock("GET", "/config.json")
+            .with_body(format!(
+                "{{\"dl\":\"{0}/dl\",\"api\":\"{0}\"}}",
+                server.url()
+            ))
+            .create_async()
+            .await;
+        let api = server
+            .mock("GET", "/api/v1/crates/my-crate")
+            .with_body(
+                r#"{
+                    "crate": {
+                        "description": "An internal crate",
+                        "repository": "https://github.com/company/my-crate",
+                        "updated_at": "2025-01-02T00:00:00Z"
+                    },
+                    "versions": [
+                        {
+                            "num": "0.1.0",
+                            "license": "MIT",
+                            "created_at": "2024-03-04T00:00:00Z",
+                            "published_by": {"login": "jdoe", "name": "Jane Doe"}
+                        }
+                    ]
+                }"#,
+            )
+            .create_async()
+            .await;
+
+        let registry = Registry::from_url(&format!("sparse+{}/", server.url()));
+        let client = reqwest::Client::new();
+        let entry = generate_biblatex_registry(
+            "my-crate",
+            Some("0.1"),
+            &crate::ResolveOptions::default(),
+            &registry,
+            &client,
+        )
+        .await?;
+        index.assert_async().await;
+        config.assert_async().await;
+        api.assert_async().await;
+
+        assert_eq!(entry.key, "Doe2025");
+        assert_eq!(entry.author_names(), "Jane Doe");
+        assert_eq!(entry.title, "{my-crate}: An internal crate");
+        assert_eq!(entry.version, Some(semver::Version::new(0, 1, 0)));
+        assert_eq!(entry.license.as_deref(), Some("MIT"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn registry_without_web_api_access() -> crate::Result<()> {
+        let mut server = mockito::Server::new_async().await;
+        let _index = server
+            .mock("GET", "/my/-c/my-crate")
+            .with_body("{\"name\":\"my-crate\",\"vers\":\"0.1.0\",\"deps\":[]}\n")
+            .create_async()
+            .await;
+        let _config = server
+            .mock("GET", "/config.json")
+            .with_body(format!(
+                "{{\"dl\":\"{0}/dl\",\"api\":\"{0}\"}}",
+                server.url()
+            ))
+            .create_async()
+            .await;
+        let api = server
+            .mock("GET", "/api/v1/crates/my-crate")
+            .with_status(401)
+            .create_async()
+            .await;
+
+        let registry = Registry::from_url(&format!("sparse+{}/", server.url()));
+        let entry = generate_biblatex_registry(
+            "my-crate",
+            None,
+            &crate::ResolveOptions::default(),
+            &registry,
+            &reqwest::Client::new(),
+        )
+        .await?;
+        api.assert_async().await;
+        assert_eq!(entry.version, Some(semver::Version::new(0, 1, 0)));
+        Ok(())
+    }
+
+    #[test]
+    fn read_token_from_credentials() {
+        let credentials = [
+            "[registry]\ntoken = \"crates-io-token\"\n".to_string(),
+            "[registries.my-registry]\ntoken = \"secret\"\n".to_string(),
+        ];
+        assert_eq!(
+            credentials_token("my-registry", &credentials).as_deref(),
+            Some("secret")
+        );
+        assert_eq!(credentials_token("other", &credentials), None);
+    }
+}
@@ -0,0 +1,179 @@
+use serde::{Deserialize, Serialize};
+
+use crate::{BibLaTeX, MergedBibLaTeX, Source};
+
+/// Severity of a [Diagnostic]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Severity {
+    /// The citation was obtained but may need attention
+    Warning,
+    /// No citation could be obtained
+    Error,
+}
+
+/// A message which accompanies the results of a citation
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Diagnostic {
+    /// How severe the problem is
+    pub severity: Severity,
+    /// Human-readable description of the problem
+    pub message: String,
+}
+
+impl Diagnostic {
+    /// Creates a [Severity::Warning]
+    pub fn warning(message: impl Into<String>) -> Self {
+        Diagnostic {
+            severity: Severity::Warning,
+            message: message.into(),
+        }
+    }
+
+    /// Creates a [Severity::Error]
+    pub fn error(message: impl Into<String>) -> Self {
+        Diagnostic {
+            severity: Severity::Error,
+            message: message.into(),
+        }
+    }
+}
+
+impl BibLaTeX {
+    /// Warnings about the cited version such as yanked releases or advisories
+    pub fn diagnostics(&self, crate_name: &str) -> Vec<Diagnostic> {
+        let mut diagnostics = vec![];
+        if let BibLaTeX::CratesIO(b) = self {
+            if b.yanked {
+                diagnostics.push(Diagnostic::warning(format!(
+                    "the cited version {} of {crate_name} was yanked",
+                    b.version.as_ref().map_or("".to_string(), |v| v.to_string()),
+                )));
+            }
+            for notice in b.notices.iter() {
+                diagnostics.push(Diagnostic::warning(format!("{crate_name} is {notice}")));
+            }
+        }
+        diagnostics
+    }
+}
+
+/// A single result together with how it was obtained
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct RankedResult {
+    /// Position of the result starting at 1 for the preferred result
+    pub rank: usize,
+    /// Where the result was obtained from
+    pub source: Source,
+    /// The formatted BibLaTeX entries of the result
+    pub biblatex: String,
+    /// The full result
+    pub result: BibLaTeX,
+}
+
+/// Machine-readable summary of all results obtained for a single crate
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct CitationReport {
+    /// Name of the cited crate
+    pub crate_name: String,
+    /// Version requirement with which the crate was cited
+    pub version: Option<String>,
+    /// Citation key in the document if the crate was found by [crate::scan_documents]
+    pub cite_key: Option<String>,
+    /// All results in the order of their preference
+    pub results: Vec<RankedResult>,
+    /// The combined entry together with the provenance of its fields if results were merged
+    pub merged: Option<MergedBibLaTeX>,
+    /// Warnings and errors which occurred while citing the crate
+    pub diagnostics: Vec<Diagnostic>,
+}
+
+impl CitationReport {
+    /// Ranks the results in the given order and collects their [BibLaTeX::diagnostics]
+    pub fn new(crate_name: &str, version: Option<&str>, results: &[BibLaTeX]) -> Self {
+        CitationReport {
+            crate_name: crate_name.to_string(),
+            version: version.map(|v| v.to_string()),
+            cite_key: None,
+            results: results
+                .iter()
+                .enumerate()
+                .map(|(n, result)| RankedResult {
+                    rank: n + 1,
+                    source: result.source(),
+                    biblatex: result.to_string(),
+                    result: result.clone(),
+                })
+                .collect(),
+            merged: None,
+            diagnostics: results
+                .iter()
+                .flat_map(|r| r.diagnostics(crate_name))
+                .collect(),
+        }
+    }
+
+    /// Creates a report without results for a crate which could not be cited
+    pub fn from_error(crate_name: &str, version: Option<&str>, error: &crate::Err) -> Self {
+        let mut report = CitationReport::new(crate_name, version, &[]);
+        report
+            .diagnostics
+            .push(Diagnostic::error(error.to_string()));
+        report
+    }
+
+    /// Serializes the report as indented JSON
+    pub fn to_json(&self) -> crate::Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Serializes the report into a single line as used by [JSON Lines](https://jsonlines.org)
+    pub fn to_json_line(&self) -> crate::Result<String> {
+        Ok(serde_json::to_string(self)?)
+    }
+}
+
+/// Serializes multiple reports as an indented JSON array
+pub fn reports_to_json(reports: &[CitationReport]) -> crate::Result<String> {
+    Ok(serde_json::to_string_pretty(reports)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_ranks_and_diagnostics() -> crate::Result<()> {
+        let results = vec![
+            BibLaTeX::Zenodo(crate::BibLaTeXCratesIO {
+                key: "Tolnay2025".to_string(),
+                title: "{serde}".to_string(),
+                doi: Some("10.5281/zenodo.1".to_string()),
+                ..Default::default()
+            }),
+            BibLaTeX::CratesIO(crate::BibLaTeXCratesIO {
+                key: "Tolnay2025".to_string(),
+                title: "{serde}".to_string(),
+                version: Some(semver::Version::new(1, 0, 0)),
+                yanked: true,
+                ..Default::default()
+            }),
+        ];
+        let report = CitationReport::new("serde", Some("1"), &results);
+        assert_eq!(report.results[0].rank, 1);
+        assert_eq!(report.results[1].source, Source::CratesIO);
+        assert_eq!(
+            report.diagnostics,
+            vec![Diagnostic::warning(
+                "the cited version 1.0.0 of serde was yanked"
+            )]
+        );
+
+        let line = report.to_json_line()?;
+        assert!(!line.contains('\n'));
+        let value: serde_json::Value = serde_json::from_str(&line)?;
+        assert_eq!(value["results"][0]["source"], "Zenodo");
+        assert_eq!(value["results"][1]["result"]["CratesIO"]["yanked"], true);
+        assert_eq!(value["diagnostics"][0]["severity"], "Warning");
+        Ok(())
+    }
+}
@@ -0,0 +1,345 @@
+use serde::{Deserialize, Serialize};
+
+/// Options which control how a version is selected from all published versions of a crate
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct ResolveOptions {
+    /// Also consider versions which were yanked.
+    ///
+    /// Yanked versions are always considered if the requirement pins a version exactly
+    /// (eg. `=0.2.3`).
+    pub include_yanked: bool,
+    /// Also consider pre-releases such as `1.0.0-alpha.1`
+    pub include_prerelease: bool,
+    /// Select the oldest instead of the newest matching version
+    pub oldest: bool,
+    /// Query the RustSec advisories which mark the selected version as unmaintained
+    /// (see [crate::rustsec_notices]).
+    ///
+    /// This requires an additional request per crate and is therefore disabled by default.
+    pub advisories: bool,
+}
+
+/// A published version of a crate which can be selected by [resolve_version]
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct VersionCandidate {
+    /// The version number as published
+    pub num: String,
+    /// Indicates if this version was yanked
+    pub yanked: bool,
+}
+
+/// The reason why a version was or was not selected by [resolve_version]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Reason {
+    /// This version was selected
+    Selected,
+    /// Another matching version was preferred
+    NotPreferred,
+    /// The version does not match the requirement
+    NoMatch,
+    /// The version was explicitly excluded by a `!=` requirement
+    Excluded,
+    /// The version was yanked and [ResolveOptions::include_yanked] is not set
+    Yanked,
+    /// The version is a pre-release and [ResolveOptions::include_prerelease] is not set
+    Prerelease,
+    /// The version number is not a valid semver version
+    Unparsable,
+}
+
+impl std::fmt::Display for Reason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Reason::Selected => "selected",
+            Reason::NotPreferred => "matches but another version was preferred",
+            Reason::NoMatch => "does not match the requirement",
+            Reason::Excluded => "explicitly excluded",
+            Reason::Yanked => "yanked",
+            Reason::Prerelease => "pre-release",
+            Reason::Unparsable => "not a valid semver version",
+        })
+    }
+}
+
+/// A version which was considered during [resolve_version]
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct ConsideredVersion {
+    /// The version number as published
+    pub num: String,
+    /// Why this version was or was not selected
+    pub reason: Reason,
+}
+
+/// The outcome of [resolve_version]
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct VersionResolution {
+    /// Index of the selected version in the list of candidates
+    pub index: usize,
+    /// The selected version
+    pub version: semver::Version,
+    /// All versions which were considered in the order in which they were given
+    pub considered: Vec<ConsideredVersion>,
+}
+
+impl std::fmt::Display for VersionResolution {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Selected version {}", self.version)?;
+        for ConsideredVersion { num, reason } in self.considered.iter() {
+            writeln!(f, "    {num}: {reason}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A version requirement in the syntax of `Cargo.toml` such as `>=0.3, <0.5`.
+///
+/// In addition to [semver::VersionReq], versions can be excluded explicitly via `!=0.2.3`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VersionRequirement {
+    /// All comparators which need to match
+    pub req: semver::VersionReq,
+    /// Versions which are explicitly excluded
+    pub excluded: Vec<semver::Version>,
+}
+
+impl VersionRequirement {
+    /// Parses a comma-separated list of comparators
+    pub fn parse(requirement: &str) -> crate::Result<Self> {
+        let mut comparators = vec![];
+        let mut excluded = vec![];
+        for part in requirement.split(',').map(|x| x.trim()) {
+            if let Some(version) = part.strip_prefix("!=") {
+                excluded.push(semver::Version::parse(version.trim())?);
+            } else if !part.is_empty() {
+                comparators.push(part);
+            }
+        }
+        let req = if comparators.is_empty() {
+            semver::VersionReq::STAR
+        } else {
+            semver::VersionReq::parse(&comparators.join(", "))?
+        };
+        Ok(Self { req, excluded })
+    }
+
+    /// Checks if the requirement pins a single version exactly such as `=0.2.3`
+    pub fn is_exact(&self) -> bool {
+        match self.req.comparators.as_slice() {
+            [c] => c.op == semver::Op::Exact && c.minor.is_some() && c.patch.is_some(),
+            _ => false,
+        }
+    }
+
+    /// Checks if the version matches the requirement.
+    ///
+    /// Pre-releases which are not allowed by [semver::VersionReq] are compared by their
+    /// precedence if `include_prerelease` is set.
+    /// Thus `0.4.1-alpha` satisfies `^0.4` while `0.4.0-alpha` sorts below `0.4.0` and does not.
+    fn matches(&self, version: &semver::Version, include_prerelease: bool) -> bool {
+        if self.req.matches(version) {
+            return true;
+        }
+        if include_prerelease && !version.pre.is_empty() {
+            let mut release = version.clone();
+            release.pre = semver::Prerelease::EMPTY;
+            return self.req.matches(&release)
+                && self
+                    .req
+                    .comparators
+                    .iter()
+                    .all(|c| above_lower_bound(c, version));
+        }
+        false
+    }
+}
+
+/// Checks that the version does not sort below the smallest version allowed by the comparator
+fn above_lower_bound(comparator: &semver::Comparator, version: &semver::Version) -> bool {
+    use semver::Op;
+    let bound = semver::Version {
+        major: comparator.major,
+        minor: comparator.minor.unwrap_or(0),
+        patch: comparator.patch.unwrap_or(0),
+        pre: comparator.pre.clone(),
+        build: semver::BuildMetadata::EMPTY,
+    };
+    match comparator.op {
+        Op::Less | Op::LessEq => true,
+        Op::Greater if comparator.patch.is_some() => version > &bound,
+        Op::Greater => true,
+        _ => version >= &bound,
+    }
+}
+
+/// Selects a version from the given candidates which matches the version requirement.
+///
+/// If no requirement is given, any version is accepted.
+/// The returned [VersionResolution] lists all versions which were considered together with the
+/// [Reason] why they were or were not selected.
+pub fn resolve_version(
+    crate_name: &str,
+    candidates: &[VersionCandidate],
+    requirement: Option<&str>,
+    options: &ResolveOptions,
+) -> crate::Result<VersionResolution> {
+    #[cfg(feature = "log")]
+    log::trace!("Filter versions");
+    let parsed_requirement = requirement.map(VersionRequirement::parse).transpose()?;
+    let parsed_requirement = parsed_requirement.unwrap_or(VersionRequirement {
+        req: semver::VersionReq::STAR,
+        excluded: vec![],
+    });
+
+    let include_yanked = options.include_yanked || parsed_requirement.is_exact();
+
+    let mut considered = vec![];
+    let mut matching = vec![];
+    for (n, candidate) in candidates.iter().enumerate() {
+        let reason = match semver::Version::parse(&candidate.num) {
+            Err(_) => Reason::Unparsable,
+            Ok(v) if parsed_requirement.excluded.contains(&v) => Reason::Excluded,
+            Ok(v) if !parsed_requirement.matches(&v, options.include_prerelease) => {
+                if !v.pre.is_empty() && parsed_requirement.matches(&v, true) {
+                    Reason::Prerelease
+                } else {
+                    Reason::NoMatch
+                }
+            }
+            Ok(_) if candidate.yanked && !include_yanked => Reason::Yanked,
+            Ok(v) => {
+                matching.push((n, v));
+                Reason::NotPreferred
+            }
+        };
+        considered.push(ConsideredVersion {
+            num: candidate.num.clone(),
+            reason,
+        });
+    }
+
+    let selected = if options.oldest {
+        matching.into_iter().min_by(|a, b| a.1.cmp(&b.1))
+    } else {
+        matching.into_iter().max_by(|a, b| a.1.cmp(&b.1))
+    };
+    let (index, version) = selected.ok_or(crate::NotFoundError(
+        requirement.map_or(format!("Could not find crate {crate_name}"), |x| {
+            format!("Could not find version {x} for crate {crate_name}")
+        }),
+    ))?;
+    considered[index].reason = Reason::Selected;
+
+    let resolution = VersionResolution {
+        index,
+        version,
+        considered,
+    };
+    #[cfg(feature = "log")]
+    log::info!("Resolved version of crate {crate_name}\n{resolution}");
+    Ok(resolution)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidates(versions: &[(&str, bool)]) -> Vec<VersionCandidate> {
+        versions
+            .iter()
+            .map(|(num, yanked)| VersionCandidate {
+                num: num.to_string(),
+                yanked: *yanked,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn resolve_requirements() -> crate::Result<()> {
+        let c = candidates(&[
+            ("0.2.2", false),
+            ("0.2.3", false),
+            ("0.2.4", true),
+            ("0.3.0", false),
+            ("0.4.1", false),
+            ("0.5.0-alpha.1", false),
+            ("0.5.0", false),
+        ]);
+        let opts = ResolveOptions::default();
+        let resolve = |req: Option<&str>, opts: &ResolveOptions| -> crate::Result<String> {
+            Ok(resolve_version("c", &c, req, opts)?.version.to_string())
+        };
+        assert_eq!(resolve(None, &opts)?, "0.5.0");
+        assert_eq!(resolve(Some("0.2"), &opts)?, "0.2.3");
+        assert_eq!(resolve(Some(">=0.3, <0.5"), &opts)?, "0.4.1");
+        assert_eq!(resolve(Some("0.2.*, !=0.2.3"), &opts)?, "0.2.2");
+        assert_eq!(resolve(Some("=0.2.2"), &opts)?, "0.2.2");
+        assert_eq!(resolve(Some("=0.2.4"), &opts)?, "0.2.4");
+
+        let yanked = ResolveOptions {
+            include_yanked: true,
+            ..Default::default()
+        };
+        assert_eq!(resolve(Some("0.2"), &yanked)?, "0.2.4");
+
+        let oldest = ResolveOptions {
+            oldest: true,
+            ..Default::default()
+        };
+        assert_eq!(resolve(Some(">=0.3"), &oldest)?, "0.3.0");
+
+        let prerelease = ResolveOptions {
+            include_prerelease: true,
+            ..Default::default()
+        };
+        assert_eq!(resolve(Some(">=0.4, <0.5"), &prerelease)?, "0.4.1");
+        assert_eq!(resolve(Some("^0.5"), &prerelease)?, "0.5.0");
+        let pre = candidates(&[
+            ("0.3.2", false),
+            ("0.4.0-alpha", false),
+            ("0.4.1-rc.1", false),
+        ]);
+        let resolve_pre = |req: &str| -> crate::Result<String> {
+            Ok(resolve_version("c", &pre, Some(req), &prerelease)?
+                .version
+                .to_string())
+        };
+        // 0.4.0-alpha sorts below 0.4.0 and thus does not satisfy ^0.4
+        assert_eq!(resolve_pre("^0.4")?, "0.4.1-rc.1");
+        assert!(resolve_pre("~0.4.1").is_err());
+        assert_eq!(resolve_pre(">=0.4.0-alpha, <0.4.1")?, "0.4.0-alpha");
+        assert_eq!(resolve_pre("<0.4")?, "0.3.2");
+        assert_eq!(resolve_pre(">0.3.2")?, "0.4.1-rc.1");
+        assert_eq!(resolve(Some("0.5.0"), &opts)?, "0.5.0");
+        assert!(resolve(Some("0.6"), &opts).is_err());
+        assert!(resolve(Some("not a version"), &opts).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn resolution_reasons() -> crate::Result<()> {
+        let c = candidates(&[
+            ("0.1.0", false),
+            ("0.2.1-beta", false),
+            ("0.2.0", true),
+            ("0.2.1", false),
+            ("0.2.2", false),
+            ("invalid", false),
+        ]);
+        let resolution =
+            resolve_version("c", &c, Some("0.2, !=0.2.2"), &ResolveOptions::default())?;
+        let reasons: Vec<_> = resolution.considered.iter().map(|c| c.reason).collect();
+        assert_eq!(
+            reasons,
+            vec![
+                Reason::NoMatch,
+                Reason::Prerelease,
+                Reason::Yanked,
+                Reason::Selected,
+                Reason::Excluded,
+                Reason::Unparsable,
+            ]
+        );
+        assert_eq!(resolution.index, 3);
+        Ok(())
+    }
+}
@@ -0,0 +1,262 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::BibLaTeX;
+
+/// The prefix of citation keys which refer to crates such as in `\cite{crate:serde}`
+pub const DEFAULT_CITE_PREFIX: &str = "crate:";
+
+/// A crate which is cited in a document by its citation key
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct CitedCrate {
+    /// The full citation key such as `crate:serde@1.0`
+    pub key: String,
+    /// Name of the crate
+    pub name: String,
+    /// Version requirement given after `@` in the key
+    pub version: Option<String>,
+}
+
+impl CitedCrate {
+    /// Parses a citation key of the form `{prefix}{name}` or `{prefix}{name}@{version}`
+    pub fn parse(key: &str, prefix: &str) -> Option<Self> {
+        let rest = key.strip_prefix(prefix)?;
+        let (name, version) = match rest.split_once('@') {
+            Some((name, version)) => (name, Some(version.to_string())),
+            None => (rest, None),
+        };
+        let valid = !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        valid.then(|| CitedCrate {
+            key: key.to_string(),
+            name: name.to_string(),
+            version: version.filter(|v| !v.is_empty()),
+        })
+    }
+}
+
+/// Finds all keys of LaTeX citation commands such as `\cite{a,b}`, `\textcite[p. 2]{a}` or
+/// `\nocite{a}`
+pub fn find_latex_cite_keys(text: &str) -> Vec<String> {
+    let mut keys = vec![];
+    // Comments are not part of the document
+    let text: String = text
+        .lines()
+        .map(|line| {
+            // A percent sign is escaped by an odd number of preceding backslashes such that
+            // `\\%` is a line break followed by a comment
+            let mut backslashes = 0;
+            let end = line
+                .char_indices()
+                .find(|(_, c)| {
+                    let comment = *c == '%' && backslashes % 2 == 0;
+                    backslashes = if *c == '\\' { backslashes + 1 } else { 0 };
+                    comment
+                })
+                .map_or(line.len(), |(n, _)| n);
+            &line[..end]
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    for (n, _) in text.match_indices('\\') {
+        let rest = &text[n + 1..];
+        let command: String = rest
+            .chars()
+            .take_while(|c| c.is_ascii_alphabetic())
+            .collect();
+        if !command.to_lowercase().contains("cite") {
+            continue;
+        }
+        let mut rest = rest[command.len()..].trim_start_matches('*').trim_start();
+        // Skip optional pre- and postnotes
+        while let Some(r) = rest.strip_prefix('[') {
+            let Some(end) = r.find(']') else {
+                break;
+            };
+            rest = r[end + 1..].trim_start();
+        }
+        let Some(r) = rest.strip_prefix('{') else {
+            continue;
+        };
+        let Some(end) = r.find('}') else {
+            continue;
+        };
+        keys.extend(
+            r[..end]
+                .split(',')
+                .map(|k| k.trim().to_string())
+                .filter(|k| !k.is_empty()),
+        );
+    }
+    keys
+}
+
+/// Finds all keys of [Pandoc citations](https://pandoc.org/MANUAL.html#citation-syntax) such as
+/// `[@crate:serde; @other, p. 3]` or `@crate:serde` in Markdown
+pub fn find_pandoc_cite_keys(text: &str) -> Vec<String> {
+    let mut keys = vec![];
+    for (n, _) in text.match_indices('@') {
+        let at_boundary = text[..n]
+            .chars()
+            .last()
+            .is_none_or(|c| c.is_whitespace() || matches!(c, '[' | ';' | '-' | '('));
+        if !at_boundary {
+            continue;
+        }
+        let rest = &text[n + 1..];
+        let rest = rest.strip_prefix('{').unwrap_or(rest);
+        let key: String = rest
+            .chars()
+            .take_while(|c| !c.is_whitespace() && !matches!(c, ']' | ';' | ',' | '}' | ')'))
+            .collect();
+        let key = key.trim_end_matches(['.', ':', '?', '!']);
+        if !key.is_empty() {
+            keys.push(key.to_string());
+        }
+    }
+    keys
+}
+
+/// Finds all crates cited in a document with the given key prefix.
+///
+/// Files ending in `.md`, `.qmd` or `.rmd` are scanned for Pandoc citations while all other files
+/// are treated as LaTeX.
+/// Every key is only returned once in the order in which it appears.
+pub fn find_cited_crates(filename: &str, text: &str, prefix: &str) -> Vec<CitedCrate> {
+    let lower = filename.to_lowercase();
+    let keys = if [".md", ".qmd", ".rmd"].iter().any(|e| lower.ends_with(e)) {
+        find_pandoc_cite_keys(text)
+    } else {
+        find_latex_cite_keys(text)
+    };
+    let mut cited: Vec<CitedCrate> = vec![];
+    for c in keys.iter().filter_map(|k| CitedCrate::parse(k, prefix)) {
+        if !cited.iter().any(|x| x.key == c.key) {
+            cited.push(c);
+        }
+    }
+    cited
+}
+
+/// Reads all given documents and collects the crates cited in them (see [find_cited_crates])
+pub fn scan_documents(paths: &[&Path], prefix: &str) -> crate::Result<Vec<CitedCrate>> {
+    let mut cited: Vec<CitedCrate> = vec![];
+    for path in paths {
+        #[cfg(feature = "log")]
+        log::trace!("Scanning {} for citations", path.display());
+        let text = std::fs::read_to_string(path)?;
+        for c in find_cited_crates(&path.to_string_lossy(), &text, prefix) {
+            if !cited.iter().any(|x| x.key == c.key) {
+                cited.push(c);
+            }
+        }
+    }
+    Ok(cited)
+}
+
+/// Obtain BibLaTeX entries for every cited crate via [crate::get_biblatex_with_options].
+///
+/// The version requirement of the citation key is resolved according to the given
+/// [crate::ResolveOptions].
+/// Failures of individual crates are reported alongside the crate and do not abort the
+/// remaining crates.
+pub async fn get_biblatex_cited(
+    cited: &[CitedCrate],
+    options: &crate::ResolveOptions,
+    user_agent: Option<&str>,
+    filenames: Vec<&str>,
+) -> crate::Result<Vec<(CitedCrate, crate::Result<Vec<BibLaTeX>>)>> {
+    let client = crate::build_client(user_agent)?;
+    let crates_io_client = crate::build_crates_io_client(&client);
+    let mut results = vec![];
+    for c in cited {
+        #[cfg(feature = "log")]
+        log::trace!("Obtaining entries for cited crate {}", c.key);
+        let r = crate::get_biblatex_with_clients(
+            &c.name,
+            c.version.as_deref(),
+            options,
+            &client,
+            &crates_io_client,
+            None,
+            filenames.clone(),
+        )
+        .await;
+        results.push((c.clone(), r));
+    }
+    Ok(results)
+}
+
+/// Selects the preferred entry for every cited crate and renames it to the citation key used in
+/// the document
+pub fn cited_entries(
+    results: &[(CitedCrate, crate::Result<Vec<BibLaTeX>>)],
+) -> crate::Result<Vec<biblatex::Entry>> {
+    let mut entries = vec![];
+    for (cited, result) in results {
+        let Ok(result) = result else {
+            continue;
+        };
+        if let Some(first) = result.first() {
+            if let Some(mut entry) = crate::result_entries(first)?.into_iter().next() {
+                entry.key = cited.key.clone();
+                entries.push(entry);
+            }
+        }
+    }
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn scan_latex_citations() {
+        let tex = r#"
+We use \cite{crate:serde} and \textcite[see][p. 3]{other, crate:rand@0.8}.
+% \cite{crate:commented}
+A line break\\% \cite{crate:after_break}
+Load 50\% of \parencite*{ crate:serde , crate:cellular_raza@=0.2.4 }.
+\nocite{crate:tokio}\ref{crate:notacite}
+"#;
+        let cited = find_cited_crates("paper.tex", tex, DEFAULT_CITE_PREFIX);
+        let keys: Vec<_> = cited.iter().map(|c| c.key.as_str()).collect();
+        assert_eq!(
+            keys,
+            vec![
+                "crate:serde",
+                "crate:rand@0.8",
+                "crate:cellular_raza@=0.2.4",
+                "crate:tokio"
+            ]
+        );
+        assert_eq!(
+            cited[2],
+            CitedCrate {
+                key: "crate:cellular_raza@=0.2.4".to_string(),
+                name: "cellular_raza".to_string(),
+                version: Some("=0.2.4".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn scan_pandoc_citations() {
+        let md = "As shown by @crate:serde, and [@rust:rand@0.8; @other, p. 4].\n\
+            Contact me@example.com or see @crate:tokio.";
+        let keys: Vec<_> = find_cited_crates("paper.qmd", md, "crate:")
+            .into_iter()
+            .map(|c| c.key)
+            .collect();
+        assert_eq!(keys, vec!["crate:serde", "crate:tokio"]);
+        let keys: Vec<_> = find_cited_crates("README.MD", md, "rust:")
+            .into_iter()
+            .map(|c| (c.name, c.version))
+            .collect();
+        assert_eq!(keys, vec![("rand".to_string(), Some("0.8".to_string()))]);
+    }
+}
@@ -0,0 +1,273 @@
+use biblatex::ChunksExt;
+
+use crate::{BibLaTeX, PlainBibLaTeX};
+
+/// Information about the cited crate which is used to select entries of a bibliography
+#[derive(Clone, Debug, Default)]
+pub struct SelectionContext {
+    /// Name of the crate or repository
+    pub name: Option<String>,
+    /// Link to the repository
+    pub repository: Option<String>,
+    /// The [DOI](https://www.doi.org/) specified in the `CITATION.cff` file
+    pub doi: Option<String>,
+}
+
+fn normalize_name(name: &str) -> String {
+    name.to_lowercase()
+        .replace("\\_", "_")
+        .replace(['-', '_', '{', '}'], " ")
+}
+
+pub(crate) fn normalize_url(url: &str) -> String {
+    url.trim()
+        .trim_start_matches("https://")
+        .trim_start_matches("http://")
+        .trim_start_matches("www.")
+        .trim_end_matches('/')
+        .trim_end_matches(".git")
+        .to_lowercase()
+}
+
+fn is_preferred(entry: &biblatex::Entry) -> bool {
+    let field = |key| entry.get(key).map(|v| v.format_verbatim().to_lowercase());
+    matches!(field("preferred").as_deref(), Some("true" | "yes" | "1"))
+        || field("keywords").is_some_and(|k| {
+            k.split([',', ';'])
+                .any(|k| matches!(k.trim(), "preferred" | "cite-this" | "preferred-citation"))
+        })
+}
+
+/// Scores how well the entry cites the crate described by the context.
+///
+/// | Criterion | Score |
+/// | --- | --- |
+/// | Marked as preferred via `preferred = {true}` or keyword `preferred` | 100 |
+/// | DOI matches the DOI of the `CITATION.cff` file | 50 |
+/// | Url matches the repository | 40 |
+/// | Title contains the name of the crate | 30 |
+/// | Entry type is `@software` | 20 |
+/// | Key contains the name of the crate | 10 |
+pub fn score_entry(entry: &biblatex::Entry, context: &SelectionContext) -> u32 {
+    let mut score = 0;
+    if is_preferred(entry) {
+        score += 100;
+    }
+    if let (Some(doi), Ok(entry_doi)) = (&context.doi, entry.doi()) {
+        if crate::normalize_doi(doi).eq_ignore_ascii_case(crate::normalize_doi(&entry_doi)) {
+            score += 50;
+        }
+    }
+    if let (Some(repository), Ok(url)) = (&context.repository, entry.url()) {
+        if normalize_url(repository) == normalize_url(&url) {
+            score += 40;
+        }
+    }
+    if let Some(name) = context.name.as_ref().map(|n| normalize_name(n)) {
+        if entry
+            .title()
+            .is_ok_and(|t| normalize_name(&t.format_verbatim()).contains(&name))
+        {
+            score += 30;
+        }
+        if normalize_name(&entry.key).contains(&name) {
+            score += 10;
+        }
+    }
+    if entry.entry_type == biblatex::EntryType::Software {
+        score += 20;
+    }
+    score
+}
+
+/// Ranks all entries of a bibliography by [score_entry] in descending order
+pub fn rank_entries<'a>(
+    bibliography: &'a biblatex::Bibliography,
+    context: &SelectionContext,
+) -> Vec<(&'a biblatex::Entry, u32)> {
+    let mut ranked: Vec<_> = bibliography
+        .iter()
+        .map(|e| (e, score_entry(e, context)))
+        .collect();
+    ranked.sort_by_key(|(_, score)| u32::MAX - score);
+    ranked
+}
+
+impl PlainBibLaTeX {
+    /// Selects the entries with the highest [score_entry].
+    ///
+    /// Returns `false` if the bibliography contains multiple entries of which none matches the
+    /// context such as the references of a paper.
+    /// In this case nothing is selected.
+    /// A bibliography with a single entry is always used as a whole.
+    pub fn select(&mut self, context: &SelectionContext) -> bool {
+        let ranked = rank_entries(&self.bibliography, context);
+        let best = ranked.first().map_or(0, |(_, score)| *score);
+        let cited = best > 0 || ranked.len() <= 1;
+        self.selected = if best == 0 || ranked.len() == 1 {
+            vec![]
+        } else {
+            ranked
+                .into_iter()
+                .take_while(|(_, score)| *score == best)
+                .map(|(e, _)| e.key.clone())
+                .collect()
+        };
+        cited
+    }
+
+    /// All selected entries or the whole bibliography if nothing was selected
+    pub fn selected_entries(&self) -> Vec<&biblatex::Entry> {
+        if self.selected.is_empty() {
+            self.bibliography.iter().collect()
+        } else {
+            self.selected
+                .iter()
+                .filter_map(|key| self.bibliography.get(key))
+                .collect()
+        }
+    }
+}
+
+/// Selects the entries citing the crate in every bibliography of the results.
+///
+/// The DOI of a `CITATION.cff` file in the results is used to identify matching entries.
+/// Bibliographies such as `paper/paper.bib` in which no entry cites the crate are removed since
+/// they only list related works.
+pub fn select_cited_entries(results: &mut Vec<BibLaTeX>, name: Option<&str>, repository: &str) {
+    let doi = results.iter().find_map(|r| match r {
+        BibLaTeX::CITATIONCFF(cff) => cff
+            .preferred_citation
+            .as_ref()
+            .and_then(|p| p.doi.clone())
+            .or(cff.doi.clone()),
+        _ => None,
+    });
+    let context = SelectionContext {
+        name: name.map(|n| n.to_string()),
+        repository: Some(repository.to_string()),
+        doi,
+    };
+    results.retain_mut(|result| match result {
+        BibLaTeX::Plain(plain) => {
+            let cited = plain.select(&context);
+            #[cfg(feature = "log")]
+            if !cited {
+                log::info!(
+                    "Skipping {} since none of its entries cites the crate",
+                    plain.filename
+                );
+            }
+            cited
+        }
+        _ => true,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BIBLIOGRAPHY: &str = r#"
+@article{Other2020,
+    author = {Doe, John},
+    title = {Related work on agent-based modeling},
+    doi = {10.1000/other},
+}
+@software{cellular_raza_software,
+    author = {Pleyer, Jonas},
+    title = {cellular\_raza},
+    url = {https://github.com/jonaspleyer/cellular_raza},
+}
+@article{Pleyer_2025,
+    author = {Pleyer, Jonas and Fleck, Christian},
+    title = {cellular\_raza: Cellular Agent-based Modeling from a Clean Slate},
+    doi = {10.21105/joss.07723},
+}
+"#;
+
+    fn plain() -> PlainBibLaTeX {
+        PlainBibLaTeX {
+            bibliography: biblatex::Bibliography::parse(BIBLIOGRAPHY).unwrap(),
+            repository: "https://github.com/jonaspleyer/cellular_raza".to_string(),
+            filename: "citation.bib".to_string(),
+            doi: None,
+            selected: vec![],
+            discovered: false,
+        }
+    }
+
+    #[test]
+    fn select_by_name_and_url() {
+        let mut plain = plain();
+        plain.select(&SelectionContext {
+            name: Some("cellular-raza".to_string()),
+            repository: Some("https://github.com/jonaspleyer/cellular_raza/".to_string()),
+            doi: None,
+        });
+        assert_eq!(plain.selected, vec!["cellular_raza_software"]);
+        assert_eq!(plain.bibliography.len(), 3);
+        let output = format!("{}", BibLaTeX::Plain(plain));
+        assert!(output.contains("cellular_raza_software"));
+        assert!(!output.contains("Other2020"));
+    }
+
+    #[test]
+    fn select_by_doi_and_preference() {
+        let mut plain = plain();
+        let context = SelectionContext {
+            name: Some("cellular_raza".to_string()),
+            repository: None,
+            doi: Some("https://doi.org/10.21105/joss.07723".to_string()),
+        };
+        plain.select(&context);
+        assert_eq!(plain.selected, vec!["Pleyer_2025"]);
+
+        plain.bibliography.get_mut("Other2020").unwrap().set(
+            "keywords",
+            vec![biblatex::Spanned::detached(biblatex::Chunk::Normal(
+                "modeling, preferred".to_string(),
+            ))],
+        );
+        plain.select(&context);
+        assert_eq!(plain.selected, vec!["Other2020"]);
+
+        plain.bibliography.remove("Other2020");
+        plain.bibliography.remove("cellular_raza_software");
+        plain.bibliography.insert(
+            biblatex::Bibliography::parse("@article{Unrelated, title = {Other}}")
+                .unwrap()
+                .remove("Unrelated")
+                .unwrap(),
+        );
+        assert!(!plain.select(&SelectionContext::default()));
+        assert!(plain.selected.is_empty());
+        assert_eq!(plain.selected_entries().len(), 2);
+    }
+
+    #[test]
+    fn drop_unrelated_bibliographies() {
+        let mut unrelated = plain();
+        unrelated.filename = "paper/paper.bib".to_string();
+        unrelated.bibliography.remove("cellular_raza_software");
+        unrelated.bibliography.remove("Pleyer_2025");
+        unrelated.bibliography.insert(
+            biblatex::Bibliography::parse("@book{Murray2002, title = {Mathematical Biology}}")
+                .unwrap()
+                .remove("Murray2002")
+                .unwrap(),
+        );
+        let mut results = vec![
+            BibLaTeX::Plain(unrelated),
+            BibLaTeX::Plain(plain()),
+            BibLaTeX::CratesIO(crate::BibLaTeXCratesIO::default()),
+        ];
+        select_cited_entries(
+            &mut results,
+            Some("cellular_raza"),
+            "https://github.com/jonaspleyer/cellular_raza",
+        );
+        assert_eq!(results.len(), 2);
+        assert!(matches!(&results[0], BibLaTeX::Plain(p) if p.filename == "citation.bib"));
+    }
+}
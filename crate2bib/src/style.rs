@@ -0,0 +1,354 @@
+use serde::{Deserialize, Serialize};
+
+use crate::BibLaTeX;
+
+/// Citation styles in which references can be formatted by [format_reference]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum CitationStyle {
+    /// [APA](https://apastyle.apa.org) 7th edition
+    Apa,
+    /// [IEEE](https://journals.ieeeauthorcenter.ieee.org) reference style
+    Ieee,
+    /// [Chicago](https://www.chicagomanualofstyle.org) author-date style
+    Chicago,
+    /// Vancouver style as used by the [NLM](https://www.nlm.nih.gov)
+    Vancouver,
+}
+
+impl std::str::FromStr for CitationStyle {
+    type Err = crate::Err;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "apa" => Ok(CitationStyle::Apa),
+            "ieee" => Ok(CitationStyle::Ieee),
+            "chicago" => Ok(CitationStyle::Chicago),
+            "vancouver" => Ok(CitationStyle::Vancouver),
+            x => Err(crate::Err::InvalidValue(format!(
+                "the {x} citation style is currently not supported"
+            ))),
+        }
+    }
+}
+
+/// Markup in which formatted references are written
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub enum Markup {
+    /// Plain text without any emphasis
+    #[default]
+    Text,
+    /// Markdown with `*emphasis*`
+    Markdown,
+    /// HTML with `<i>emphasis</i>` and links
+    Html,
+}
+
+impl std::str::FromStr for Markup {
+    type Err = crate::Err;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" | "plain" | "txt" => Ok(Markup::Text),
+            "markdown" | "md" => Ok(Markup::Markdown),
+            "html" => Ok(Markup::Html),
+            x => Err(crate::Err::InvalidValue(format!(
+                "the {x} markup is currently not supported"
+            ))),
+        }
+    }
+}
+
+impl Markup {
+    fn text(&self, text: &str) -> String {
+        match self {
+            Markup::Text => text.to_string(),
+            Markup::Markdown => text.replace('*', "\\*"),
+            Markup::Html => text
+                .replace('&', "&amp;")
+                .replace('<', "&lt;")
+                .replace('>', "&gt;")
+                .replace('"', "&quot;"),
+        }
+    }
+
+    fn emph(&self, text: &str) -> String {
+        match self {
+            Markup::Text => self.text(text),
+            Markup::Markdown => format!("*{}*", self.text(text)),
+            Markup::Html => format!("<i>{}</i>", self.text(text)),
+        }
+    }
+
+    fn link(&self, url: &str) -> String {
+        match self {
+            Markup::Text => url.to_string(),
+            Markup::Markdown => format!("<{url}>"),
+            Markup::Html => format!("<a href=\"{0}\">{0}</a>", self.text(url)),
+        }
+    }
+}
+
+impl CitationStyle {
+    /// The bundled [CSL](https://citationstyles.org) style
+    pub fn csl(&self) -> CslStyle {
+        use ::hayagriva::archive::ArchivedStyle;
+        let archived = match self {
+            CitationStyle::Apa => ArchivedStyle::AmericanPsychologicalAssociation,
+            CitationStyle::Ieee => ArchivedStyle::InstituteOfElectricalAndElectronicsEngineers,
+            CitationStyle::Chicago => ArchivedStyle::ChicagoAuthorDate,
+            CitationStyle::Vancouver => ArchivedStyle::Vancouver,
+        };
+        match archived.get() {
+            ::hayagriva::citationberg::Style::Independent(style) => style,
+            // All styles above are independent styles
+            ::hayagriva::citationberg::Style::Dependent(_) => unreachable!(),
+        }
+    }
+}
+
+/// A [CSL](https://citationstyles.org) style which can be used by [format_references_csl]
+pub type CslStyle = ::hayagriva::citationberg::IndependentStyle;
+
+/// Loads a custom style from the contents of a `.csl` file.
+///
+/// Dependent styles which only refer to a parent style are not supported.
+pub fn csl_style_from_xml(xml: &str) -> crate::Result<CslStyle> {
+    match ::hayagriva::citationberg::Style::from_xml(xml)
+        .map_err(|e| crate::Err::Csl(format!("{e}")))?
+    {
+        ::hayagriva::citationberg::Style::Independent(style) => Ok(style),
+        ::hayagriva::citationberg::Style::Dependent(d) => Err(crate::Err::Csl(format!(
+            "dependent styles are not supported; use the parent style {}",
+            d.parent_link.href
+        ))),
+    }
+}
+
+fn write_children(children: &::hayagriva::ElemChildren, markup: Markup, out: &mut String) {
+    let mut children = children.0.iter().peekable();
+    while let Some(child) = children.next() {
+        // Styles write links such as DOIs as a prefix followed by the link itself
+        if let (
+            ::hayagriva::ElemChild::Text(prefix),
+            Some(::hayagriva::ElemChild::Link { text, url }),
+        ) = (child, children.peek())
+        {
+            if format!("{}{}", prefix.text, text.text) == *url {
+                out.push_str(&markup.link(url));
+                children.next();
+                continue;
+            }
+        }
+        write_child(child, markup, out);
+    }
+}
+
+fn write_child(child: &::hayagriva::ElemChild, markup: Markup, out: &mut String) {
+    use ::hayagriva::citationberg::FontStyle;
+    match child {
+        ::hayagriva::ElemChild::Text(t) if t.formatting.font_style == FontStyle::Italic => {
+            out.push_str(&markup.emph(&t.text))
+        }
+        ::hayagriva::ElemChild::Text(t) => out.push_str(&markup.text(&t.text)),
+        ::hayagriva::ElemChild::Elem(e) => write_children(&e.children, markup, out),
+        ::hayagriva::ElemChild::Markup(m) => out.push_str(&markup.text(m)),
+        ::hayagriva::ElemChild::Link { url, .. } => out.push_str(&markup.link(url)),
+        ::hayagriva::ElemChild::Transparent { .. } => (),
+    }
+}
+
+/// Formats entries as references with the given [CSL](https://citationstyles.org) style.
+///
+/// The references are ordered as prescribed by the style and separated by empty lines.
+pub fn format_references_csl<'a>(
+    entries: impl IntoIterator<Item = &'a biblatex::Entry>,
+    style: &CslStyle,
+    markup: Markup,
+) -> crate::Result<String> {
+    let yaml = crate::entries_to_hayagriva(entries)?;
+    let library =
+        ::hayagriva::io::from_yaml_str(&yaml).map_err(|e| crate::Err::Csl(format!("{e}")))?;
+    let locales = ::hayagriva::archive::locales();
+    let mut driver = ::hayagriva::BibliographyDriver::new();
+    driver.citation(::hayagriva::CitationRequest::from_items(
+        library
+            .iter()
+            .map(::hayagriva::CitationItem::with_entry)
+            .collect(),
+        style,
+        &locales,
+    ));
+    let rendered = driver.finish(::hayagriva::BibliographyRequest::new(style, None, &locales));
+    Ok(rendered
+        .bibliography
+        .map(|b| b.items)
+        .unwrap_or_default()
+        .iter()
+        .map(|item| {
+            let mut out = String::new();
+            if let Some(first) = &item.first_field {
+                write_child(first, markup, &mut out);
+                out.push(' ');
+            }
+            write_children(&item.content, markup, &mut out);
+            out.trim_end().to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n"))
+}
+
+/// Formats an entry as a reference in the given [CitationStyle] and [Markup].
+///
+/// The bundled [CSL](https://citationstyles.org) styles are rendered by
+/// [Hayagriva](https://github.com/typst/hayagriva).
+/// Custom `.csl` files can be loaded with [csl_style_from_xml] and used with
+/// [format_references_csl].
+pub fn format_reference(
+    entry: &biblatex::Entry,
+    style: CitationStyle,
+    markup: Markup,
+) -> crate::Result<String> {
+    format_references_csl([entry], &style.csl(), markup)
+}
+
+/// Formats multiple entries with [format_reference] separated by empty lines
+pub fn format_references<'a>(
+    entries: impl IntoIterator<Item = &'a biblatex::Entry>,
+    style: CitationStyle,
+    markup: Markup,
+) -> crate::Result<String> {
+    format_references_csl(entries, &style.csl(), markup)
+}
+
+impl BibLaTeX {
+    /// Formats all entries of this result as references (see [format_reference])
+    pub fn format_references(&self, style: CitationStyle, markup: Markup) -> crate::Result<String> {
+        format_references(&crate::result_entries(self)?, style, markup)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn software() -> biblatex::Entry {
+        crate::BibLaTeXCratesIO {
+            key: "Pleyer2025".to_string(),
+            work_type: "software".to_string(),
+            author: vec![
+                crate::person_from_name("Jonas Pleyer"),
+                crate::person_from_name("Christian Fleck"),
+            ],
+            title: "{cellular_raza}: Cellular Agent-based Modeling".to_string(),
+            url: Some("https://github.com/jonaspleyer/cellular_raza".to_string()),
+            doi: Some("10.5281/zenodo.15549".to_string()),
+            version: Some(semver::Version::new(0, 2, 4)),
+            date: chrono::NaiveDate::from_ymd_opt(2025, 6, 1)
+                .and_then(|d| d.and_hms_opt(0, 0, 0))
+                .map(|d| d.and_utc()),
+            ..Default::default()
+        }
+        .to_entry()
+    }
+
+    fn article() -> biblatex::Entry {
+        biblatex::Bibliography::parse(
+            "@article{Pleyer_2025,
+                author = {Pleyer, Jonas and Fleck, Christian},
+                title = {cellular\\_raza: Cellular Agent-based Modeling from a Clean Slate},
+                journal = {Journal of Open Source Software},
+                volume = {10}, number = {110}, pages = {7723}, year = {2025},
+                doi = {10.21105/joss.07723},
+            }",
+        )
+        .unwrap()
+        .into_vec()
+        .remove(0)
+    }
+
+    #[test]
+    fn format_software_styles() -> crate::Result<()> {
+        let entry = software();
+        for (style, expected) in [
+            (
+                CitationStyle::Apa,
+                "Pleyer, J., & Fleck, C. (2025). cellular_raza: Cellular Agent-based Modeling \
+                [Computer software]. https://doi.org/10.5281/zenodo.15549",
+            ),
+            (
+                CitationStyle::Ieee,
+                "[1] J. Pleyer and C. Fleck, cellular_raza: Cellular Agent-based Modeling. \
+                (June 01, 2025). doi: https://doi.org/10.5281/zenodo.15549.",
+            ),
+            (
+                CitationStyle::Chicago,
+                "Pleyer, Jonas, and Christian Fleck. 2025. Cellular_raza: Cellular Agent-Based \
+                Modeling. Released June 1. https://doi.org/10.5281/zenodo.15549.",
+            ),
+            (
+                CitationStyle::Vancouver,
+                "1. Pleyer J, Fleck C. cellular_raza: Cellular Agent-based Modeling [Internet]. \
+                2025. Available from: https://github.com/jonaspleyer/cellular_raza",
+            ),
+        ] {
+            assert_eq!(format_reference(&entry, style, Markup::Text)?, expected);
+        }
+        assert_eq!(
+            format_reference(&entry, CitationStyle::Apa, Markup::Html)?,
+            "Pleyer, J., &amp; Fleck, C. (2025). <i>cellular_raza: Cellular Agent-based \
+            Modeling</i> [Computer software]. \
+            <a href=\"https://doi.org/10.5281/zenodo.15549\">\
+            https://doi.org/10.5281/zenodo.15549</a>"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn format_article_styles() -> crate::Result<()> {
+        let entry = article();
+        assert_eq!(
+            format_reference(&entry, CitationStyle::Apa, Markup::Markdown)?,
+            "Pleyer, J., & Fleck, C. (2025). cellular_raza: Cellular Agent-based Modeling from \
+            a Clean Slate. *Journal of Open Source Software*, *10*(110), 7723. \
+            <https://doi.org/10.21105/joss.07723>"
+        );
+        assert_eq!(
+            format_reference(&entry, CitationStyle::Ieee, Markup::Text)?,
+            "[1] J. Pleyer and C. Fleck, \u{201c}cellular_raza: Cellular Agent-based Modeling \
+            from a Clean Slate,\u{201d} Journal of Open Source Software, vol. 10, no. 110, \
+            p. 7723, 2025, doi: https://doi.org/10.21105/joss.07723."
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn load_custom_csl_style() -> crate::Result<()> {
+        let style = csl_style_from_xml(
+            r#"<?xml version="1.0" encoding="utf-8"?>
+            <style xmlns="http://purl.org/net/xbiblio/csl" class="in-text" version="1.0">
+              <info>
+                <title>Title and year</title>
+                <id>title-and-year</id>
+                <updated>2025-01-01T00:00:00+00:00</updated>
+              </info>
+              <citation><layout><text variable="title"/></layout></citation>
+              <bibliography>
+                <layout suffix=".">
+                  <text variable="title" font-style="italic"/>
+                  <date variable="issued" prefix=", "><date-part name="year"/></date>
+                </layout>
+              </bibliography>
+            </style>"#,
+        )?;
+        assert_eq!(
+            format_references_csl([&article(), &software()], &style, Markup::Markdown)?,
+            "*cellular_raza: Cellular Agent-based Modeling from a Clean Slate*, 2025.\n\n\
+            *cellular_raza: Cellular Agent-based Modeling*, 2025."
+        );
+        assert!(matches!(
+            csl_style_from_xml("<style/>"),
+            Err(crate::Err::Csl(_))
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,380 @@
+use serde::{Deserialize, Serialize};
+
+use crate::BibLaTeXCratesIO;
+
+/// Base url of the [Software Heritage](https://www.softwareheritage.org) archive
+pub const SWH_API_URL: &str = "https://archive.softwareheritage.org";
+
+/// Type of an object archived by [Software Heritage](https://www.softwareheritage.org)
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub enum SwhObjectType {
+    /// A commit
+    Revision,
+    /// The source tree of a commit
+    Directory,
+}
+
+/// A [Software Heritage persistent identifier](https://docs.softwareheritage.org/devel/swh-model/persistent-identifiers.html)
+/// such as `swh:1:rev:309cf2674ee7a0749978cf8265ab91a60aea0f7d`
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Swhid {
+    /// Type of the identified object
+    pub object_type: SwhObjectType,
+    /// Intrinsic `sha1_git` hash of the object
+    pub hash: String,
+}
+
+impl std::fmt::Display for Swhid {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let object_type = match self.object_type {
+            SwhObjectType::Revision => "rev",
+            SwhObjectType::Directory => "dir",
+        };
+        write!(f, "swh:1:{object_type}:{}", self.hash)
+    }
+}
+
+/// Status of a "save code now" request (see [swh_save_code_now])
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct SwhSaveRequest {
+    /// Either `accepted`, `rejected` or `pending`
+    pub save_request_status: String,
+    /// Status of the archival such as `not yet scheduled`, `running` or `succeeded`
+    pub save_task_status: Option<String>,
+}
+
+/// Formats the repository in the same way as origins are stored by Software Heritage
+fn swh_origin(repository: &str) -> String {
+    match crate::github_owner_repo(repository) {
+        Some((owner, repo)) => format!("https://github.com/{owner}/{repo}"),
+        None => repository
+            .trim_end_matches('/')
+            .trim_end_matches(".git")
+            .to_string(),
+    }
+}
+
+async fn swh_get(
+    client: &reqwest::Client,
+    base_url: &str,
+    path: &str,
+) -> crate::Result<Option<serde_json::Value>> {
+    let url = format!("{}/api/1/{path}", base_url.trim_end_matches('/'));
+    #[cfg(feature = "log")]
+    log::trace!("Requesting {url}");
+    let response = client.get(url).send().await?;
+    if response.status() == reqwest::StatusCode::NOT_FOUND {
+        return Ok(None);
+    }
+    Ok(Some(response.error_for_status()?.json().await?))
+}
+
+fn get_str<'a>(value: &'a serde_json::Value, key: &str) -> Option<&'a str> {
+    value.get(key).and_then(|x| x.as_str())
+}
+
+/// Looks up the [Swhid] of the revision of a repository.
+///
+/// The `reference` may either be a full commit hash or the name of a tag or branch.
+/// If no reference is given, the `HEAD` of the most recent snapshot is used.
+/// Returns [None] if the repository or revision was not archived yet.
+pub async fn swhid_lookup(
+    client: &reqwest::Client,
+    base_url: &str,
+    repository: &str,
+    reference: Option<&str>,
+) -> crate::Result<Option<Swhid>> {
+    let revision = |hash: &str| Swhid {
+        object_type: SwhObjectType::Revision,
+        hash: hash.to_string(),
+    };
+    if let Some(commit) =
+        reference.filter(|r| r.len() == 40 && r.chars().all(|c| c.is_ascii_hexdigit()))
+    {
+        return Ok(swh_get(client, base_url, &format!("revision/{commit}/"))
+            .await?
+            .map(|_| revision(commit)));
+    }
+
+    let origin = swh_origin(repository);
+    let Some(visit) = swh_get(
+        client,
+        base_url,
+        &format!("origin/{origin}/visit/latest/?require_snapshot=true"),
+    )
+    .await?
+    else {
+        return Ok(None);
+    };
+    let Some(snapshot) = get_str(&visit, "snapshot") else {
+        return Ok(None);
+    };
+    let Some(snapshot) = swh_get(client, base_url, &format!("snapshot/{snapshot}/")).await? else {
+        return Ok(None);
+    };
+    let branches = snapshot.get("branches");
+    let branch = |name: &str| branches.and_then(|b| b.get(name));
+
+    let mut target = match reference {
+        Some(r) => [
+            format!("refs/tags/{r}"),
+            format!("refs/heads/{r}"),
+            r.to_string(),
+        ]
+        .iter()
+        .find_map(|name| branch(name)),
+        None => branch("HEAD"),
+    }
+    .cloned();
+    // Follow aliases such as HEAD -> refs/heads/main and releases to their revision.
+    // The number of steps is limited to guard against cyclic aliases.
+    for _ in 0..8 {
+        let Some(t) = target.take() else {
+            break;
+        };
+        let id = get_str(&t, "target").unwrap_or_default();
+        match get_str(&t, "target_type") {
+            Some("revision") => return Ok(Some(revision(id))),
+            Some("alias") => target = branch(id).cloned(),
+            Some("release") => {
+                target = swh_get(client, base_url, &format!("release/{id}/")).await?;
+            }
+            _ => (),
+        }
+    }
+    Ok(None)
+}
+
+/// Checks if the repository was already archived by
+/// [Software Heritage](https://www.softwareheritage.org)
+pub async fn swh_origin_exists(
+    client: &reqwest::Client,
+    base_url: &str,
+    repository: &str,
+) -> crate::Result<bool> {
+    let origin = swh_origin(repository);
+    Ok(swh_get(client, base_url, &format!("origin/{origin}/get/"))
+        .await?
+        .is_some())
+}
+
+/// Requests the archival of a repository which is not yet archived by
+/// [Software Heritage](https://www.softwareheritage.org).
+pub async fn swh_save_code_now(
+    client: &reqwest::Client,
+    base_url: &str,
+    repository: &str,
+) -> crate::Result<SwhSaveRequest> {
+    let url = format!(
+        "{}/api/1/origin/save/git/url/{}/",
+        base_url.trim_end_matches('/'),
+        swh_origin(repository)
+    );
+    #[cfg(feature = "log")]
+    log::info!("Requesting archival of {repository} by Software Heritage");
+    Ok(client
+        .post(url)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await?)
+}
+
+/// Looks up the [Swhid] of the cited version and adds it to the entry.
+///
+/// If no `reference` is given, the tags `v{version}`, `{version}` and `{crate_name}-v{version}`
+/// are tried.
+/// If the repository was not archived at all and `save` is set, a "save code now" request is
+/// sent.
+pub async fn add_swhid(
+    client: &reqwest::Client,
+    base_url: &str,
+    entry: &mut BibLaTeXCratesIO,
+    crate_name: Option<&str>,
+    reference: Option<&str>,
+    save: bool,
+) -> crate::Result<Option<Swhid>> {
+    let Some(repository) = entry.url.clone() else {
+        return Ok(None);
+    };
+    let references = match (reference, &entry.version) {
+        (Some(r), _) => vec![Some(r.to_string())],
+        (None, Some(v)) => {
+            let mut tags = vec![Some(format!("v{v}")), Some(format!("{v}"))];
+            tags.extend(crate_name.map(|name| Some(format!("{name}-v{v}"))));
+            tags
+        }
+        (None, None) => vec![None],
+    };
+    for reference in references.iter() {
+        if let Some(swhid) =
+            swhid_lookup(client, base_url, &repository, reference.as_deref()).await?
+        {
+            entry.swhid = Some(swhid.to_string());
+            return Ok(Some(swhid));
+        }
+    }
+    if save && !swh_origin_exists(client, base_url, &repository).await? {
+        let _request = swh_save_code_now(client, base_url, &repository).await?;
+        #[cfg(feature = "log")]
+        log::info!(
+            "Archival of {repository} is {}",
+            _request.save_request_status
+        );
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const REV: &str = "309cf2674ee7a0749978cf8265ab91a60aea0f7d";
+
+    async fn mock_server() -> mockito::ServerGuard {
+        let mut server = mockito::Server::new_async().await;
+        let origin = "https://github.com/jonaspleyer/cellular_raza";
+        server
+            .mock(
+                "GET",
+                format!("/api/1/origin/{origin}/visit/latest/").as_str(),
+            )
+            .match_query(mockito::Matcher::Any)
+            .with_body(r#"{"snapshot": "abc", "status": "full"}"#)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/api/1/snapshot/abc/")
+            .with_body(format!(
+                r#"{{"branches": {{
+                    "HEAD": {{"target": "refs/heads/master", "target_type": "alias"}},
+                    "refs/heads/master": {{"target": "{REV}", "target_type": "revision"}},
+                    "refs/tags/v0.2.4": {{"target": "def", "target_type": "release"}},
+                    "refs/tags/cellular_raza-v0.2.5": {{"target": "def", "target_type": "release"}}
+                }}}}"#
+            ))
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/api/1/release/def/")
+            .with_body(format!(
+                r#"{{"target": "{REV}", "target_type": "revision"}}"#
+            ))
+            .create_async()
+            .await;
+        server
+    }
+
+    #[tokio::test]
+    async fn lookup_swhid() -> crate::Result<()> {
+        let server = mock_server().await;
+        let client = reqwest::Client::new();
+        let repository = "https://github.com/jonaspleyer/cellular_raza.git";
+        for reference in [None, Some("v0.2.4"), Some("master")] {
+            let swhid = swhid_lookup(&client, &server.url(), repository, reference).await?;
+            assert_eq!(
+                swhid.map(|s| s.to_string()),
+                Some(format!("swh:1:rev:{REV}")),
+                "{reference:?}"
+            );
+        }
+        assert_eq!(
+            swhid_lookup(&client, &server.url(), repository, Some("v0.3.0")).await?,
+            None
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn add_swhid_and_save() -> crate::Result<()> {
+        let mut server = mock_server().await;
+        let client = reqwest::Client::new();
+        let mut entry = BibLaTeXCratesIO {
+            url: Some("https://github.com/jonaspleyer/cellular_raza".to_string()),
+            version: Some(semver::Version::new(0, 2, 4)),
+            ..Default::default()
+        };
+        add_swhid(&client, &server.url(), &mut entry, None, None, false).await?;
+        assert_eq!(entry.swhid, Some(format!("swh:1:rev:{REV}")));
+        assert!(format!("{entry}").contains("eprinttype = {swh},"));
+
+        let mut entry = BibLaTeXCratesIO {
+            url: Some("https://github.com/jonaspleyer/cellular_raza".to_string()),
+            version: Some(semver::Version::new(0, 2, 5)),
+            ..Default::default()
+        };
+        add_swhid(
+            &client,
+            &server.url(),
+            &mut entry,
+            Some("cellular_raza"),
+            None,
+            false,
+        )
+        .await?;
+        assert_eq!(entry.swhid, Some(format!("swh:1:rev:{REV}")));
+
+        // The origin is archived but the tag is unknown so no archival is requested
+        let origin = server
+            .mock(
+                "GET",
+                "/api/1/origin/https://github.com/jonaspleyer/cellular_raza/get/",
+            )
+            .with_body(r#"{"url": "https://github.com/jonaspleyer/cellular_raza"}"#)
+            .create_async()
+            .await;
+        let no_save = server
+            .mock("POST", mockito::Matcher::Any)
+            .expect(0)
+            .create_async()
+            .await;
+        let mut entry = BibLaTeXCratesIO {
+            url: Some("https://github.com/jonaspleyer/cellular_raza".to_string()),
+            version: Some(semver::Version::new(0, 3, 0)),
+            ..Default::default()
+        };
+        assert!(
+            add_swhid(&client, &server.url(), &mut entry, None, None, true)
+                .await?
+                .is_none()
+        );
+        origin.assert_async().await;
+        no_save.assert_async().await;
+        no_save.remove_async().await;
+
+        server
+            .mock(
+                "GET",
+                "/api/1/origin/https://gitlab.com/owner/repo/visit/latest/",
+            )
+            .match_query(mockito::Matcher::Any)
+            .with_status(404)
+            .create_async()
+            .await;
+        server
+            .mock("GET", "/api/1/origin/https://gitlab.com/owner/repo/get/")
+            .with_status(404)
+            .create_async()
+            .await;
+        let save = server
+            .mock(
+                "POST",
+                "/api/1/origin/save/git/url/https://gitlab.com/owner/repo/",
+            )
+            .with_body(r#"{"save_request_status": "accepted", "save_task_status": "pending"}"#)
+            .create_async()
+            .await;
+        let mut entry = BibLaTeXCratesIO {
+            url: Some("https://gitlab.com/owner/repo".to_string()),
+            ..Default::default()
+        };
+        assert!(
+            add_swhid(&client, &server.url(), &mut entry, None, None, true)
+                .await?
+                .is_none()
+        );
+        save.assert_async().await;
+        Ok(())
+    }
+}
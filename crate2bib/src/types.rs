@@ -20,6 +20,18 @@ pub struct PlainBibLaTeX {
     pub repository: String,
     /// Name of the file where the citation was discovered
     pub filename: String,
+    /// The DOI which was resolved via [doi.org](https://doi.org) to obtain this bibliography
+    pub doi: Option<String>,
+    /// Keys of the entries which cite the crate (see [PlainBibLaTeX::select]).
+    /// If empty, the whole bibliography is used.
+    #[serde(default)]
+    pub selected: Vec<String>,
+    /// Indicates that the DOI was found in the README or manifest (see [crate::discover_dois])
+    /// instead of a citation file.
+    /// Such DOIs frequently refer to related works and are therefore ranked below all other
+    /// results.
+    #[serde(default)]
+    pub discovered: bool,
 }
 
 /// Envoked if a certain file or entity can not be found which should be there.
@@ -65,6 +77,30 @@ pub enum Err {
     /// Wraps [biblatex::ParseError]
     #[error("error during parsing of BibLaTeX file")]
     BibLaTeXParsing(biblatex::ParseError),
+    /// Wraps [toml::de::Error]
+    #[error("error during parsing of toml file")]
+    Toml(#[from] toml::de::Error),
+    /// Wraps [serde_json::Error]
+    #[error("error during parsing of json")]
+    Json(#[from] serde_json::Error),
+    /// Wraps [std::io::Error]
+    #[error("error while reading file")]
+    Io(#[from] std::io::Error),
+    /// Wraps [crate::DoiError]
+    #[error("error while resolving DOI")]
+    Doi(#[from] crate::DoiError),
+    /// A value such as an encoding, citation style or crate specification could not be parsed
+    #[error("{0}")]
+    InvalidValue(String),
+    /// Options were given which can not be used together
+    #[error("invalid arguments: {0}")]
+    InvalidArguments(String),
+    /// A file which should be created already exists
+    #[error("file {} already exists", .0.display())]
+    FileExists(std::path::PathBuf),
+    /// A citation style could not be loaded or applied
+    #[error("error in citation style: {0}")]
+    Csl(String),
 }
 
 #[cfg(feature = "pyo3")]
@@ -82,29 +118,45 @@ impl From<biblatex::ParseError> for Err {
 
 /// Contains all variants of how a bib entry can be obtained
 #[derive(Clone, Debug, Deserialize, Serialize)]
+#[allow(clippy::large_enum_variant)]
 pub enum BibLaTeX {
-    /// Obtained bib entry form [crates.io](https://crates.io)
+    /// Obtained bib entry form [crates.io](https://crates.io) or another cargo registry
     CratesIO(BibLaTeXCratesIO),
     /// Obtained bib entry from `CITAIION.cff` inside repository.
     CITATIONCFF(citeworks_cff::Cff),
     /// Obtained bib entry directly from repository.
     Plain(PlainBibLaTeX),
+    /// Generated bib entry from the metadata of the repository.
+    Repository(BibLaTeXCratesIO),
+    /// Obtained bib entry from a `.zenodo.json` or `codemeta.json` file inside repository.
+    SoftwareMetadata(crate::SoftwareMetadata),
+    /// DOI-backed bib entry of an archived release on [Zenodo](https://zenodo.org).
+    Zenodo(BibLaTeXCratesIO),
 }
 
 impl core::fmt::Display for BibLaTeX {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.to_biblatex_string(Encoding::default()))
+    }
+}
+
+impl BibLaTeX {
+    /// Formats the entry while escaping its fields with the given [Encoding]
+    pub fn to_biblatex_string(&self, encoding: Encoding) -> String {
         match self {
-            BibLaTeX::CratesIO(b) => b.fmt(f),
+            BibLaTeX::CratesIO(b) | BibLaTeX::Repository(b) | BibLaTeX::Zenodo(b) => {
+                b.to_biblatex_string(encoding)
+            }
+            BibLaTeX::SoftwareMetadata(m) => {
+                crate::entry_to_biblatex_string(&m.to_entry(), encoding)
+            }
             BibLaTeX::CITATIONCFF(b) => {
                 let bib = BibLaTeXCratesIO::from_citation_cff(b).unwrap();
-                bib.fmt(f)
+                bib.to_biblatex_string(encoding)
+            }
+            BibLaTeX::Plain(plain) => {
+                crate::entries_to_biblatex_string(plain.selected_entries(), encoding)
             }
-            #[allow(unused)]
-            BibLaTeX::Plain(PlainBibLaTeX {
-                bibliography,
-                repository: url,
-                filename,
-            }) => f.write_str(&bibliography.to_biblatex_string()),
         }
     }
 }
@@ -115,6 +167,12 @@ impl BibLaTeX {
         match self {
             CratesIO(_) => 20,
             CITATIONCFF(_) => 10,
+            SoftwareMetadata(_) => 12,
+            Repository(_) => 15,
+            Zenodo(_) => 30,
+            Plain(PlainBibLaTeX {
+                discovered: true, ..
+            }) => 5,
             Plain(_) => 50,
         }
     }
@@ -0,0 +1,287 @@
+use crate::Encoding;
+
+/// Field which marks entries generated by this crate and stores the name of the cited crate.
+///
+/// Only entries with this field are updated by [update_bibliography].
+pub const GENERATED_MARKER_FIELD: &str = "crate2bib";
+
+/// Fields of generated entries which are refreshed by [update_bibliography]
+pub const UPDATED_FIELDS: &[&str] = &["version", "date", "url"];
+
+/// The result of [update_bibliography]
+#[derive(Clone, Debug, Default)]
+pub struct BibliographyUpdate {
+    /// The contents of the bibliography before the update
+    pub original: String,
+    /// The contents of the bibliography after the update
+    pub updated: String,
+    /// Keys of all entries which were appended
+    pub added: Vec<String>,
+    /// Keys of all generated entries whose fields changed
+    pub changed: Vec<String>,
+}
+
+impl BibliographyUpdate {
+    /// Indicates if the bibliography was left untouched
+    pub fn is_unchanged(&self) -> bool {
+        self.original == self.updated
+    }
+
+    /// Line-based diff between the original and updated bibliography.
+    ///
+    /// Removed lines start with `-`, added lines with `+` and unchanged lines with a space.
+    /// Only changed lines are shown together with up to `context` surrounding lines.
+    pub fn diff(&self, context: usize) -> String {
+        line_diff(&self.original, &self.updated, context)
+    }
+}
+
+fn line_diff(old: &str, new: &str, context: usize) -> String {
+    let a: Vec<_> = old.lines().collect();
+    let b: Vec<_> = new.lines().collect();
+    let ops = similar::capture_diff_slices(similar::Algorithm::Myers, &a, &b);
+    let mut out = String::new();
+    for (n, group) in similar::group_diff_ops(ops, context).iter().enumerate() {
+        if n > 0 || group.first().is_some_and(|op| op.old_range().start > 0) {
+            out.push_str("...\n");
+        }
+        for op in group {
+            let (tag, old_range, new_range) = op.as_tag_tuple();
+            let (c, lines) = match tag {
+                similar::DiffTag::Equal => (' ', &a[old_range]),
+                similar::DiffTag::Delete => ('-', &a[old_range]),
+                similar::DiffTag::Insert => ('+', &b[new_range]),
+                similar::DiffTag::Replace => {
+                    a[old_range]
+                        .iter()
+                        .for_each(|line| out.push_str(&format!("-{line}\n")));
+                    ('+', &b[new_range])
+                }
+            };
+            lines
+                .iter()
+                .for_each(|line| out.push_str(&format!("{c}{line}\n")));
+        }
+    }
+    out
+}
+
+/// Indentation of the fields of an entry such that inserted fields are aligned with them
+fn field_indent<'a>(text: &'a str, entry: &biblatex::RawEntry) -> &'a str {
+    entry
+        .fields
+        .first()
+        .map(|f| {
+            let start = f.key.span.start;
+            let line_start = text[..start].rfind('\n').map_or(0, |n| n + 1);
+            &text[line_start..start]
+        })
+        .filter(|indent| indent.chars().all(|c| c == ' ' || c == '\t'))
+        .unwrap_or("    ")
+}
+
+/// Adds the given entries to an existing bibliography while preserving its formatting.
+///
+/// Every entry is given together with the name of the crate which it cites.
+/// Crates which are already cited by an entry with the [GENERATED_MARKER_FIELD] or by an entry
+/// with the same key are not added again.
+/// Entries which were generated earlier have their [UPDATED_FIELDS] refreshed while all other
+/// entries, comments and `@string` macros are left untouched.
+/// New entries are appended at the end together with the [GENERATED_MARKER_FIELD].
+pub fn update_bibliography(
+    existing: &str,
+    entries: &[(String, biblatex::Entry)],
+    encoding: Encoding,
+) -> crate::Result<BibliographyUpdate> {
+    // Make sure the file is valid before modifying it
+    biblatex::Bibliography::parse(existing)?;
+    let raw = biblatex::RawBibliography::parse(existing)?;
+    let marker = |e: &biblatex::RawEntry| {
+        e.fields
+            .iter()
+            .find(|f| f.key.v.eq_ignore_ascii_case(GENERATED_MARKER_FIELD))
+            .map(|f| {
+                existing[f.value.span.clone()]
+                    .trim()
+                    .trim_matches(['{', '}', '"'])
+                    .to_string()
+            })
+    };
+
+    let mut update = BibliographyUpdate {
+        original: existing.to_string(),
+        ..Default::default()
+    };
+    let mut edits: Vec<(std::ops::Range<usize>, String)> = vec![];
+    let mut appended = vec![];
+    for (crate_name, entry) in entries.iter() {
+        let generated = raw
+            .entries
+            .iter()
+            .find(|e| marker(&e.v).as_deref() == Some(crate_name.as_str()));
+        let Some(generated) = generated else {
+            let cited = raw.entries.iter().any(|e| e.v.key.v == entry.key)
+                || update.added.contains(&entry.key);
+            if !cited {
+                let mut entry = entry.clone();
+                entry.set(
+                    GENERATED_MARKER_FIELD,
+                    vec![biblatex::Spanned::detached(biblatex::Chunk::Normal(
+                        crate_name.clone(),
+                    ))],
+                );
+                update.added.push(entry.key.clone());
+                appended.push(crate::entry_to_biblatex_string(&entry, encoding));
+            }
+            continue;
+        };
+
+        let mut changed = false;
+        let mut missing = String::new();
+        for field in UPDATED_FIELDS {
+            let Some(chunks) = entry.get(field) else {
+                continue;
+            };
+            let value = format!("{{{}}}", crate::write_value(field, chunks, encoding));
+            match generated
+                .v
+                .fields
+                .iter()
+                .find(|f| f.key.v.eq_ignore_ascii_case(field))
+            {
+                Some(f) => {
+                    if existing[f.value.span.clone()].trim() != value {
+                        edits.push((f.value.span.clone(), value));
+                        changed = true;
+                    }
+                }
+                None => {
+                    let indent = field_indent(existing, &generated.v);
+                    missing.push_str(&format!("\n{indent}{field} = {value},"));
+                    changed = true;
+                }
+            }
+        }
+        if !missing.is_empty() {
+            let body = &existing[generated.span.clone()];
+            let end = generated.span.start + body.trim_end().len();
+            if !body.trim_end().ends_with(',') {
+                missing.insert(0, ',');
+            }
+            edits.push((end..end, missing));
+        }
+        if changed {
+            update.changed.push(generated.v.key.v.to_string());
+        }
+    }
+
+    let mut updated = existing.to_string();
+    edits.sort_by_key(|(range, _)| std::cmp::Reverse(range.start));
+    for (range, value) in edits {
+        updated.replace_range(range, &value);
+    }
+    for entry in appended {
+        if !updated.is_empty() && !updated.ends_with("\n\n") {
+            updated.push_str(if updated.ends_with('\n') {
+                "\n"
+            } else {
+                "\n\n"
+            });
+        }
+        updated.push_str(&entry);
+        updated.push('\n');
+    }
+    update.updated = updated;
+    Ok(update)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXISTING: &str = r#"% Hand-curated references
+@string{joss = "Journal of Open Source Software"}
+
+@article{Pleyer_2025,
+  author  = {Pleyer, Jonas and Fleck, Christian},
+  title   = {cellular\_raza: Cellular Agent-based Modeling from a Clean Slate},
+  journal = joss,
+}
+
+@software{Tolnay2024,
+  author = {David Tolnay},
+  title = {{serde}: My manual title},
+  version = {1.0.100},
+  crate2bib = {serde}
+}
+"#;
+
+    fn entry(key: &str, title: &str, version: &str) -> biblatex::Entry {
+        crate::BibLaTeXCratesIO {
+            key: key.to_string(),
+            work_type: "software".to_string(),
+            title: title.to_string(),
+            url: Some("https://github.com/serde-rs/serde".to_string()),
+            version: semver::Version::parse(version).ok(),
+            ..Default::default()
+        }
+        .to_entry()
+    }
+
+    #[test]
+    fn update_generated_entries() -> crate::Result<()> {
+        let entries = vec![
+            (
+                "serde".to_string(),
+                entry("Tolnay2025", "{serde}: generated", "1.0.219"),
+            ),
+            (
+                "cellular_raza".to_string(),
+                entry("Pleyer_2025", "{cellular_raza}", "0.2.4"),
+            ),
+            ("rand".to_string(), entry("Rand2025", "{rand}", "0.9.0")),
+        ];
+        let update = update_bibliography(EXISTING, &entries, Encoding::Utf8)?;
+        assert_eq!(update.added, vec!["Rand2025"]);
+        assert_eq!(update.changed, vec!["Tolnay2024"]);
+        let expected = r#"% Hand-curated references
+@string{joss = "Journal of Open Source Software"}
+
+@article{Pleyer_2025,
+  author  = {Pleyer, Jonas and Fleck, Christian},
+  title   = {cellular\_raza: Cellular Agent-based Modeling from a Clean Slate},
+  journal = joss,
+}
+
+@software{Tolnay2024,
+  author = {David Tolnay},
+  title = {{serde}: My manual title},
+  version = {1.0.219},
+  crate2bib = {serde},
+  url = {https://github.com/serde-rs/serde},
+}
+
+@software{Rand2025,
+    title = {{rand}},
+    url = {https://github.com/serde-rs/serde},
+    version = {0.9.0},
+    crate2bib = {rand},
+}
+"#;
+        assert_eq!(update.updated, expected);
+        let diff = update.diff(0);
+        assert!(diff.contains("-  version = {1.0.100},\n"));
+        assert!(diff.contains("+  version = {1.0.219},\n"));
+        assert!(!diff.contains("Pleyer"));
+        let diff = update.diff(1);
+        assert!(diff.starts_with("...\n   title = {{serde}: My manual title},\n-  version"));
+        assert!(diff.contains("+  url = {https://github.com/serde-rs/serde},\n"));
+        assert!(diff.ends_with("+    crate2bib = {rand},\n }\n"));
+
+        // A second run does not change anything
+        let again = update_bibliography(&update.updated, &entries, Encoding::Utf8)?;
+        assert!(again.is_unchanged());
+        assert!(again.diff(3).is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,393 @@
+use chrono::Datelike;
+use serde::{Deserialize, Serialize};
+
+use crate::BibLaTeXCratesIO;
+
+/// Base url of [Zenodo](https://zenodo.org)
+pub const ZENODO_URL: &str = "https://zenodo.org";
+
+/// Determines how records are searched on [Zenodo](https://zenodo.org)
+#[derive(Clone, Debug)]
+pub enum ZenodoQuery<'a> {
+    /// All records which refer to the given repository
+    Repository(&'a str),
+    /// The record which was created from the github release with the given tag
+    Release {
+        /// Link to the repository
+        repository: &'a str,
+        /// Tag of the release
+        tag: &'a str,
+    },
+    /// All versions of the record with the given version or concept DOI
+    Doi(&'a str),
+}
+
+impl ZenodoQuery<'_> {
+    /// Checks if the record was created from the queried repository or release via its
+    /// related identifiers.
+    ///
+    /// Records found by their DOI are always accepted.
+    fn matches(&self, record: &ZenodoRecord) -> bool {
+        let related = || {
+            record
+                .metadata
+                .related_identifiers
+                .iter()
+                .map(|r| crate::normalize_url(&r.identifier))
+        };
+        match self {
+            ZenodoQuery::Repository(repository) => {
+                let repository = crate::normalize_url(repository);
+                let tree = format!("{repository}/tree/");
+                related().any(|r| r == repository || r.starts_with(&tree))
+            }
+            ZenodoQuery::Release { repository, tag } => {
+                let release = crate::normalize_url(&format!(
+                    "{}/tree/{tag}",
+                    repository.trim_end_matches('/')
+                ));
+                related().any(|r| r == release)
+            }
+            ZenodoQuery::Doi(_) => true,
+        }
+    }
+
+    fn to_query_string(&self) -> String {
+        match self {
+            ZenodoQuery::Repository(repository) => {
+                format!("\"{}\"", repository.trim_end_matches('/'))
+            }
+            ZenodoQuery::Release { repository, tag } => format!(
+                "related.identifier:\"{}/tree/{tag}\"",
+                repository.trim_end_matches('/')
+            ),
+            ZenodoQuery::Doi(doi) => format!("doi:\"{doi}\" OR conceptdoi:\"{doi}\""),
+        }
+    }
+}
+
+/// A person or organization which created a [ZenodoRecord]
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct ZenodoCreator {
+    /// Name in the format `Family, Given`
+    pub name: String,
+    /// The [ORCID](https://orcid.org) of the creator
+    pub orcid: Option<String>,
+    /// Affiliation of the creator
+    pub affiliation: Option<String>,
+}
+
+/// Link between a [ZenodoRecord] and another resource such as the repository it was archived from
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ZenodoRelatedIdentifier {
+    /// The identifier such as `https://github.com/owner/repo/tree/v0.1.0`
+    pub identifier: String,
+    /// How the record relates to the identifier such as `isSupplementTo`
+    pub relation: String,
+}
+
+/// License of a [ZenodoRecord]
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+pub struct ZenodoLicense {
+    /// Identifier of the license such as `mit-license`
+    pub id: String,
+}
+
+/// Metadata of a [ZenodoRecord]
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ZenodoMetadata {
+    /// Title of the record
+    pub title: String,
+    /// All creators of the record
+    pub creators: Vec<ZenodoCreator>,
+    /// Date of publication in the format `YYYY-MM-DD`
+    pub publication_date: Option<String>,
+    /// Version of the software as specified on release
+    pub version: Option<String>,
+    /// License under which the record is published
+    pub license: Option<ZenodoLicense>,
+    /// Links to related resources such as the archived repository
+    pub related_identifiers: Vec<ZenodoRelatedIdentifier>,
+}
+
+/// A single record obtained from [Zenodo](https://zenodo.org)
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ZenodoRecord {
+    /// DOI of this specific version
+    pub doi: Option<String>,
+    /// DOI which refers to all versions
+    pub conceptdoi: Option<String>,
+    /// Metadata of the record
+    pub metadata: ZenodoMetadata,
+}
+
+impl ZenodoRecord {
+    /// Parses the version of the record.
+    ///
+    /// Leading characters such as in `v0.2.4` or `my_crate-v0.2.4` are ignored.
+    pub fn semver(&self) -> Option<semver::Version> {
+        let version = self.metadata.version.as_ref()?;
+        semver::Version::parse(version.trim_start_matches(|c: char| !c.is_ascii_digit())).ok()
+    }
+
+    /// Creates a `@software` entry which is cited by the DOI of this record.
+    ///
+    /// The concept DOI is used instead if `concept` is set.
+    pub fn to_biblatex(&self, concept: bool) -> Option<BibLaTeXCratesIO> {
+        let doi = if concept {
+            self.conceptdoi.clone()
+        } else {
+            self.doi.clone()
+        }?;
+        let date = self
+            .metadata
+            .publication_date
+            .as_ref()
+            .and_then(|d| chrono::NaiveDate::parse_from_str(d, "%Y-%m-%d").ok())
+            .and_then(|d| d.and_hms_opt(0, 0, 0))
+            .map(|d| d.and_utc());
+        let author = self
+            .metadata
+            .creators
+            .iter()
+            .map(|c| match c.name.contains(", ") {
+                true => crate::person_from_name(&c.name),
+                false => crate::organization(&c.name),
+            })
+            .collect();
+        Some(BibLaTeXCratesIO {
+            key: format!(
+                "{}{}",
+                self.metadata
+                    .creators
+                    .first()
+                    .and_then(|c| c.name.split(", ").next())
+                    .unwrap_or("zenodo"),
+                date.map(|d| format!("{:4}", d.year())).unwrap_or_default()
+            ),
+            work_type: "software".to_string(),
+            author,
+            title: self.metadata.title.clone(),
+            url: Some(format!("https://doi.org/{doi}")),
+            doi: Some(doi),
+            license: self.metadata.license.as_ref().map(|l| l.id.clone()),
+            version: if concept { None } else { self.semver() },
+            date: if concept { None } else { date },
+            ..Default::default()
+        })
+    }
+}
+
+/// Searches [Zenodo](https://zenodo.org) for records matching the query.
+///
+/// All versions of matching records are returned.
+/// Since repositories are found by a full-text search, records which merely mention the
+/// repository are discarded unless one of their related identifiers refers to it.
+pub async fn zenodo_search(
+    client: &reqwest::Client,
+    base_url: &str,
+    query: &ZenodoQuery<'_>,
+) -> crate::Result<Vec<ZenodoRecord>> {
+    let url = format!("{}/api/records", base_url.trim_end_matches('/'));
+    #[cfg(feature = "log")]
+    log::trace!("Searching zenodo for {query:?}");
+    let response = client
+        .get(url)
+        .query(&[
+            ("q", query.to_query_string().as_str()),
+            ("all_versions", "true"),
+            ("size", "100"),
+        ])
+        .send()
+        .await?
+        .error_for_status()?
+        .json::<serde_json::Value>()
+        .await?;
+    let hits = response
+        .get("hits")
+        .and_then(|h| h.get("hits"))
+        .cloned()
+        .unwrap_or(serde_json::Value::Array(vec![]));
+    let records: Vec<ZenodoRecord> = serde_json::from_value(hits)?;
+    Ok(records.into_iter().filter(|r| query.matches(r)).collect())
+}
+
+/// Obtains a DOI-backed `@software` entry from [Zenodo](https://zenodo.org).
+///
+/// If a version is given, the record of exactly this version is cited.
+/// If no such record exists, the concept DOI which refers to all versions is used and a note is
+/// added to the entry.
+/// If no record was archived from the repository, [None] is returned.
+pub async fn zenodo_biblatex(
+    client: &reqwest::Client,
+    base_url: &str,
+    query: &ZenodoQuery<'_>,
+    version: Option<&semver::Version>,
+) -> crate::Result<Option<BibLaTeXCratesIO>> {
+    let records = zenodo_search(client, base_url, query).await?;
+    let exact = match version {
+        Some(v) => records.iter().find(|r| r.semver().as_ref() == Some(v)),
+        None => records.iter().max_by_key(|r| r.semver()),
+    };
+    if let Some(entry) = exact.and_then(|r| r.to_biblatex(false)) {
+        return Ok(Some(entry));
+    }
+    Ok(records
+        .first()
+        .and_then(|r| r.to_biblatex(true))
+        .map(|mut entry| {
+            #[cfg(feature = "log")]
+            log::info!("No zenodo record for the specified version. Using concept DOI.");
+            entry.note = Some(match version {
+                Some(v) => {
+                    format!("Concept DOI for all versions; no record for version {v} exists")
+                }
+                None => "Concept DOI for all versions".to_string(),
+            });
+            entry
+        }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    async fn mock_server() -> mockito::ServerGuard {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/api/records")
+            .match_query(mockito::Matcher::UrlEncoded(
+                "q".into(),
+                "\"https://github.com/jonaspleyer/cellular_raza\"".into(),
+            ))
+            .with_body(
+                r#"{"hits": {"hits": [
+                    {
+                        "doi": "10.5281/zenodo.9",
+                        "conceptdoi": "10.5281/zenodo.8",
+                        "metadata": {
+                            "title": "A paper which mentions cellular_raza",
+                            "creators": [{"name": "Doe, John"}],
+                            "publication_date": "2025-07-01",
+                            "version": "0.3.0",
+                            "related_identifiers": [{
+                                "identifier": "https://github.com/johndoe/paper",
+                                "relation": "isSupplementTo"
+                            }]
+                        }
+                    },
+                    {
+                        "doi": "10.5281/zenodo.2",
+                        "conceptdoi": "10.5281/zenodo.1",
+                        "metadata": {
+                            "title": "jonaspleyer/cellular_raza: cellular_raza-v0.2.4",
+                            "creators": [{"name": "Pleyer, Jonas", "orcid": "0009-0001-0613-7978"}],
+                            "publication_date": "2025-06-02",
+                            "version": "cellular_raza-v0.2.4",
+                            "license": {"id": "gpl-2.0"},
+                            "related_identifiers": [{
+                                "identifier": "https://github.com/jonaspleyer/cellular_raza/tree/cellular_raza-v0.2.4",
+                                "relation": "isSupplementTo"
+                            }]
+                        }
+                    },
+                    {
+                        "doi": "10.5281/zenodo.3",
+                        "conceptdoi": "10.5281/zenodo.1",
+                        "metadata": {
+                            "title": "jonaspleyer/cellular_raza: cellular_raza-v0.2.3",
+                            "creators": [{"name": "Pleyer, Jonas"}],
+                            "publication_date": "2025-03-01",
+                            "version": "cellular_raza-v0.2.3",
+                            "related_identifiers": [{
+                                "identifier": "https://github.com/jonaspleyer/cellular_raza/tree/cellular_raza-v0.2.3",
+                                "relation": "isSupplementTo"
+                            }]
+                        }
+                    }
+                ]}}"#,
+            )
+            .create_async()
+            .await;
+        server
+    }
+
+    #[tokio::test]
+    async fn zenodo_version_doi() -> crate::Result<()> {
+        let server = mock_server().await;
+        let query = ZenodoQuery::Repository("https://github.com/jonaspleyer/cellular_raza");
+        let entry = zenodo_biblatex(
+            &reqwest::Client::new(),
+            &server.url(),
+            &query,
+            Some(&semver::Version::new(0, 2, 4)),
+        )
+        .await?
+        .unwrap();
+        assert_eq!(entry.key, "Pleyer2025");
+        assert_eq!(entry.author_names(), "Jonas Pleyer");
+        assert_eq!(entry.doi.as_deref(), Some("10.5281/zenodo.2"));
+        assert_eq!(entry.version, Some(semver::Version::new(0, 2, 4)));
+        assert_eq!(entry.license.as_deref(), Some("gpl-2.0"));
+        assert!(entry.note.is_none());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn zenodo_concept_doi() -> crate::Result<()> {
+        let server = mock_server().await;
+        let query = ZenodoQuery::Repository("https://github.com/jonaspleyer/cellular_raza/");
+        let entry = zenodo_biblatex(
+            &reqwest::Client::new(),
+            &server.url(),
+            &query,
+            Some(&semver::Version::new(0, 3, 0)),
+        )
+        .await?
+        .unwrap();
+        assert_eq!(entry.doi.as_deref(), Some("10.5281/zenodo.1"));
+        assert_eq!(entry.version, None);
+        assert_eq!(
+            entry.note.as_deref(),
+            Some("Concept DOI for all versions; no record for version 0.3.0 exists")
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn zenodo_unrelated_records() -> crate::Result<()> {
+        let mut server = mockito::Server::new_async().await;
+        server
+            .mock("GET", "/api/records")
+            .match_query(mockito::Matcher::Any)
+            .with_body(
+                r#"{"hits": {"hits": [{
+                    "doi": "10.5281/zenodo.9",
+                    "conceptdoi": "10.5281/zenodo.8",
+                    "metadata": {
+                        "title": "A fork of cellular_raza",
+                        "version": "0.2.4",
+                        "related_identifiers": [{
+                            "identifier": "https://github.com/johndoe/cellular_raza/tree/v0.2.4",
+                            "relation": "isSupplementTo"
+                        }]
+                    }
+                }]}}"#,
+            )
+            .create_async()
+            .await;
+        let query = ZenodoQuery::Repository("https://github.com/jonaspleyer/cellular_raza");
+        let entry = zenodo_biblatex(
+            &reqwest::Client::new(),
+            &server.url(),
+            &query,
+            Some(&semver::Version::new(0, 2, 4)),
+        )
+        .await?;
+        assert!(entry.is_none());
+        Ok(())
+    }
+}